@@ -0,0 +1,322 @@
+use crate::baseball::{GameEvent, GameOutcome, GameStateSummary, PlayerId, TeamSide};
+use crate::win_probability::{RUN_EXPECTANCY, SCORING_PROBABILITY, WinProbabilityModel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One of the 24 base/out states, or the end of a half-inning once there are three outs.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BaseOutState {
+    pub first: bool,
+    pub second: bool,
+    pub third: bool,
+    pub outs: u8,
+}
+
+impl BaseOutState {
+    pub fn from_state_summary(state_summary: &GameStateSummary) -> Self {
+        Self {
+            first: state_summary.bases.first.is_some(),
+            second: state_summary.bases.second.is_some(),
+            third: state_summary.bases.third.is_some(),
+            outs: state_summary.outs,
+        }
+    }
+
+    /// Returns the state's row and column in a run expectancy matrix, or None once the half-inning is over.
+    fn index(&self) -> Option<(usize, usize)> {
+        let base_state = self.first as usize + 2 * self.second as usize + 4 * self.third as usize;
+        (self.outs < 3).then_some((base_state, self.outs as usize))
+    }
+}
+
+/// How many runs score over the rest of a half-inning from each base/out state, built up from the plate appearances that started in it. States that haven't come up yet fall back on the major league figures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunExpectancy {
+    runs: [[u32; 3]; 8], // scored from the state to the end of the half-inning, summed over every time it came up
+    occurrences: [[u32; 3]; 8],
+    #[serde(default)] // matrices saved before it was kept count as never having scored
+    scored: [[u32; 3]; 8], // how many of those times at least one run scored
+}
+
+impl RunExpectancy {
+    /// Adds every plate appearance in a game's history. Half-innings cut short by a walk-off are left out, since they didn't get to play out.
+    pub fn record_history(&mut self, history: &[GameEvent]) {
+        let plays = history.iter().filter_map(|event| match event {
+            GameEvent::Pitch { prev_game_state_summary, new_game_state_summary, .. }
+                | GameEvent::SacrificedOut { prev_game_state_summary, new_game_state_summary } => Some((prev_game_state_summary, new_game_state_summary)),
+            GameEvent::PitchingChange { .. } => None,
+        });
+
+        let mut half_inning_starts = Vec::new(); // the states plate appearances in the current half-inning started in
+        for (prev, new) in plays {
+            if prev.count.is_empty() {
+                half_inning_starts.push(prev);
+            }
+            if is_same_half_inning(prev, new) {
+                continue;
+            }
+            for start in half_inning_starts.drain(..) {
+                let (base_state, outs) = BaseOutState::from_state_summary(start).index().unwrap();
+                let runs = batting_runs(new, start) - batting_runs(start, start);
+                self.runs[base_state][outs] += runs as u32;
+                self.occurrences[base_state][outs] += 1;
+                self.scored[base_state][outs] += (runs > 0) as u32;
+            }
+        }
+    }
+
+    /// Adds the counts from another matrix, as when putting together batches played on different threads.
+    pub fn add(&mut self, other: &Self) {
+        for base_state in 0..8 {
+            for outs in 0..3 {
+                self.runs[base_state][outs] += other.runs[base_state][outs];
+                self.occurrences[base_state][outs] += other.occurrences[base_state][outs];
+                self.scored[base_state][outs] += other.scored[base_state][outs];
+            }
+        }
+    }
+
+    /// Returns how many runs score on average from the given state to the end of the half-inning. That's none once there are three outs.
+    pub fn value(&self, state: BaseOutState) -> f64 {
+        let Some((base_state, outs)) = state.index() else {
+            return 0.0;
+        };
+        match self.occurrences[base_state][outs] {
+            0 => RUN_EXPECTANCY[base_state][outs],
+            occurrences => self.runs[base_state][outs] as f64 / occurrences as f64,
+        }
+    }
+
+    /// Returns how often at least one run scores from the given state to the end of the half-inning.
+    pub fn scoring_probability(&self, state: BaseOutState) -> f64 {
+        let Some((base_state, outs)) = state.index() else {
+            return 0.0;
+        };
+        match self.occurrences[base_state][outs] {
+            0 => SCORING_PROBABILITY[base_state][outs],
+            occurrences => self.scored[base_state][outs] as f64 / occurrences as f64,
+        }
+    }
+
+    /// Returns how many times plate appearances have started in the given state.
+    pub fn occurrences(&self, state: BaseOutState) -> u32 {
+        state.index().map_or(0, |(base_state, outs)| self.occurrences[base_state][outs])
+    }
+
+    /// Returns the runs a play is worth to the batting side: what it scored, plus the change in run expectancy from the state before it to the state after it.
+    pub fn run_value(&self, before: BaseOutState, after: BaseOutState, runs: u8) -> f64 {
+        runs as f64 + self.value(after) - self.value(before)
+    }
+
+    /// Returns how often a gamble needs to come off to be worth trying, from the states it leaves when it works and when it doesn't, along with any runs either way scores. A steal of second with nobody out, for one, starts with a runner on first and leaves a runner on second if it works, or the bases empty with one out if not.
+    pub fn break_even_rate(&self, before: BaseOutState, success: (BaseOutState, u8), failure: (BaseOutState, u8)) -> f64 {
+        let gain = self.run_value(before, success.0, success.1);
+        let loss = self.run_value(before, failure.0, failure.1);
+        -loss / (gain - loss)
+    }
+}
+
+fn is_same_half_inning(a: &GameStateSummary, b: &GameStateSummary) -> bool {
+    a.half_inning.number == b.half_inning.number && a.half_inning.top == b.half_inning.top
+}
+
+/// Returns the runs the side batting in `batting` had in the given state.
+fn batting_runs(state: &GameStateSummary, batting: &GameStateSummary) -> u8 {
+    if batting.half_inning.top { state.visiting_team_runs } else { state.home_team_runs }
+}
+
+/// What one play was worth to the side at bat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayValue {
+    pub index: usize, // in the game's history
+    pub batting_side: TeamSide,
+    pub batter: PlayerId,
+    pub pitcher: PlayerId,
+    pub re24: f64, // runs scored plus the change in run expectancy
+    pub wpa: f64, // the change in the batting side's chances of winning
+}
+
+/// A play as it stood before and after, which is all it takes to work out its value against any run expectancy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Play {
+    pub index: usize, // in the game's history, of the event that ended the play
+    pub prev: GameStateSummary,
+    pub new: GameStateSummary,
+    pub game_outcome: GameOutcome,
+}
+
+/// Returns every play in a game's history. Pitching changes aren't plays, so they're skipped.
+pub fn plays(history: &[GameEvent]) -> Vec<Play> {
+    history.iter().enumerate()
+        .filter_map(|(index, event)| match event {
+            GameEvent::Pitch { prev_game_state_summary, events_summary, new_game_state_summary } => Some(Play {
+                index,
+                prev: prev_game_state_summary.clone(),
+                new: new_game_state_summary.clone(),
+                game_outcome: events_summary.game_outcome,
+            }),
+            GameEvent::SacrificedOut { prev_game_state_summary, new_game_state_summary } => Some(Play {
+                index,
+                prev: prev_game_state_summary.clone(),
+                new: new_game_state_summary.clone(),
+                game_outcome: GameOutcome::Ongoing,
+            }),
+            GameEvent::PitchingChange { .. } => None,
+        })
+        .collect()
+}
+
+/// Returns whether the two states are worth the same to either side: same score, inning, bases, outs and count.
+fn is_same_situation(a: &GameStateSummary, b: &GameStateSummary) -> bool {
+    is_same_half_inning(a, b)
+        && (a.home_team_runs, a.visiting_team_runs, a.outs) == (b.home_team_runs, b.visiting_team_runs, b.outs)
+        && BaseOutState::from_state_summary(a) == BaseOutState::from_state_summary(b)
+        && a.count == b.count
+}
+
+/// Joins each run of plays between the same batter and pitcher, picking up where the one before left off, into a single play. The joined plays are worth what the plays they were made from add up to, against any run expectancy, and take far less room.
+pub fn join_plays(plays: Vec<Play>) -> Vec<Play> {
+    let mut joined: Vec<Play> = Vec::new();
+    for play in plays {
+        match joined.last_mut() {
+            Some(last) if last.game_outcome == GameOutcome::Ongoing
+                && (last.prev.batter, last.prev.pitcher) == (play.prev.batter, play.prev.pitcher)
+                && is_same_half_inning(&last.prev, &last.new)
+                && is_same_situation(&last.new, &play.prev) => {
+                last.index = play.index;
+                last.new = play.new;
+                last.game_outcome = play.game_outcome;
+            },
+            _ => joined.push(play),
+        }
+    }
+    joined
+}
+
+/// Returns what each play was worth to the side at bat, in runs against the given run expectancy and in win probability worked out in the same run environment.
+pub fn evaluate(plays: &[Play], run_expectancy: &RunExpectancy) -> Vec<PlayValue> {
+    let win_probability = WinProbabilityModel::calibrated(run_expectancy);
+    plays.iter()
+        .map(|Play { index, prev, new, game_outcome }| {
+            let after = if is_same_half_inning(prev, new) {
+                BaseOutState::from_state_summary(new)
+            } else {
+                BaseOutState { first: false, second: false, third: false, outs: 3 }
+            };
+            let runs = batting_runs(new, prev) - batting_runs(prev, prev);
+            let re24 = run_expectancy.run_value(BaseOutState::from_state_summary(prev), after, runs);
+
+            let home_win_probability = match game_outcome {
+                GameOutcome::HomeTeamWins => 1.0,
+                GameOutcome::VisitingTeamWins => 0.0,
+                GameOutcome::Ongoing => win_probability.home_win_probability(new),
+            };
+            let home_wpa = home_win_probability - win_probability.home_win_probability(prev);
+            let batting_side = if prev.half_inning.top { TeamSide::Visiting } else { TeamSide::Home };
+            let wpa = if batting_side == TeamSide::Home { home_wpa } else { -home_wpa };
+
+            PlayValue { index: *index, batting_side, batter: prev.batter, pitcher: prev.pitcher, re24, wpa }
+        })
+        .collect()
+}
+
+/// Returns what each play in a game's history was worth to the side at bat, as `evaluate` does.
+pub fn evaluate_plays(history: &[GameEvent], run_expectancy: &RunExpectancy) -> Vec<PlayValue> {
+    evaluate(&plays(history), run_expectancy)
+}
+
+/// A player's plays added up, at the plate and on the mound.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerValue {
+    pub batting_re24: f64,
+    pub batting_wpa: f64,
+    pub pitching_re24: f64, // runs saved, so higher is better here too
+    pub pitching_wpa: f64,
+}
+
+impl PlayerValue {
+    pub fn add(&mut self, other: &Self) {
+        self.batting_re24 += other.batting_re24;
+        self.batting_wpa += other.batting_wpa;
+        self.pitching_re24 += other.pitching_re24;
+        self.pitching_wpa += other.pitching_wpa;
+    }
+}
+
+/// Adds up what the given plays were worth to each batter and pitcher involved.
+pub fn player_values(plays: &[PlayValue]) -> HashMap<PlayerId, PlayerValue> {
+    let mut values = HashMap::<PlayerId, PlayerValue>::new();
+    for play in plays {
+        let batter = values.entry(play.batter).or_default();
+        batter.batting_re24 += play.re24;
+        batter.batting_wpa += play.wpa;
+        let pitcher = values.entry(play.pitcher).or_default();
+        pitcher.pitching_re24 -= play.re24;
+        pitcher.pitching_wpa -= play.wpa;
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+    use crate::baseball::GameRng;
+    use crate::league::LeagueStructure;
+    use crate::sim;
+    use crate::test_support::{players, team};
+    use rand::SeedableRng;
+
+    fn base_out_state(first: bool, second: bool, third: bool, outs: u8) -> BaseOutState {
+        BaseOutState { first, second, third, outs }
+    }
+
+    #[test]
+    fn steals_of_second_need_to_work_about_seven_times_in_ten() {
+        // with nothing recorded yet, the major league figures are used
+        let run_expectancy = RunExpectancy::default();
+        let runner_on_first = base_out_state(true, false, false, 0);
+        let rate = run_expectancy.break_even_rate(runner_on_first, (base_out_state(false, true, false, 0), 0), (base_out_state(false, false, false, 1), 0));
+        assert!((0.70..0.73).contains(&rate));
+        assert_eq!(run_expectancy.value(base_out_state(true, true, true, 3)), 0.0);
+    }
+
+    #[test]
+    fn simulated_run_expectancy_falls_with_each_out() {
+        let game = Game::with_seed(LeagueStructure::default(), 5);
+        let run_expectancy = game.simulate_run_expectancy(30, 3, 5);
+        assert_eq!(game.simulate_run_expectancy(30, 1, 5), run_expectancy);
+
+        let bases_empty = |outs| base_out_state(false, false, false, outs);
+        assert!(run_expectancy.occurrences(bases_empty(0)) >= 30 * 16);
+        assert!(run_expectancy.value(bases_empty(0)) > run_expectancy.value(bases_empty(1)));
+        assert!(run_expectancy.value(bases_empty(1)) > run_expectancy.value(bases_empty(2)));
+    }
+
+    #[test]
+    fn play_values_add_up_to_what_happened() {
+        let simulated_game = sim::simulate_game(players(), team("home", 0), team("visiting", 100), &mut GameRng::seed_from_u64(11));
+        let mut run_expectancy = RunExpectancy::default();
+        run_expectancy.record_history(&simulated_game.history);
+        let plays = evaluate_plays(&simulated_game.history, &run_expectancy);
+
+        // win probability starts even and ends at the result, whatever happened in between
+        let home_wpa = plays.iter().map(|play| if play.batting_side == TeamSide::Home { play.wpa } else { -play.wpa }).sum::<f64>();
+        let home_won = simulated_game.result.home_team_runs > simulated_game.result.visiting_team_runs;
+        assert!((home_wpa - if home_won { 0.5 } else { -0.5 }).abs() < 1e-9);
+
+        // the visitors bat through every half-inning, so their RE24 comes to their runs less what they'd expect from that many fresh half-innings
+        let visiting_plays = plays.iter().filter(|play| play.batting_side == TeamSide::Visiting).collect::<Vec<_>>();
+        let half_innings = simulated_game.box_score.visiting.line.runs_by_inning.len() as f64;
+        let expected = simulated_game.result.visiting_team_runs as f64 - half_innings * run_expectancy.value(base_out_state(false, false, false, 0));
+        assert!((visiting_plays.iter().map(|play| play.re24).sum::<f64>() - expected).abs() < 1e-9);
+
+        // every play counts once for the batter and once against the pitcher
+        let values = player_values(&plays);
+        let batter = plays[0].batter;
+        let batter_re24 = plays.iter().filter(|play| play.batter == batter).map(|play| play.re24).sum::<f64>();
+        assert!((values.get(&batter).unwrap().batting_re24 - batter_re24).abs() < 1e-9);
+        let starter_wpa = plays.iter().filter(|play| play.pitcher == PlayerId(9)).map(|play| play.wpa).sum::<f64>();
+        assert!((values.get(&PlayerId(9)).unwrap().pitching_wpa + starter_wpa).abs() < 1e-9);
+    }
+}
@@ -1,12 +1,37 @@
-use crate::baseball::{BaseballGame, BatterDecision, EventsSummary, GameOutcome, GameStateSummary, Player, PlayerMetrics, StrikeZoneLocation, Team};
-use crate::text::{Granularity, TextEngine};
-use prompted::input;
+use crate::analytics::{self, PlayValue, PlayerValue, RunExpectancy};
+use crate::baseball::{BaseballGame, BatterDecision, Contract, Domain, EventsSummary, GameEvent, GameOutcome, GameRng, GameStateSummary, LineupError, Player, PlayerId, PlayerMetrics, Position, StrikeZoneLocation, Team, TeamSide};
+use crate::batch::{self, BatchTotals};
+use crate::box_score::BoxScore;
+use crate::demon::{self, DemonPower, Ritual};
+use crate::development;
+use crate::event_log::{EventLog, LogEntry};
+use crate::league::LeagueStructure;
+use crate::postseason::{Postseason, PostseasonConfig, SeriesSlot};
+use crate::season::{DivisionStandings, GameResult, GameSlot, Season, SeasonConfig, StandingsRow};
+use crate::sim::{self, SimulatedGame};
+use crate::stats::{Award, StatBook, StatCategory, StatScope};
+use crate::text::{self, Granularity, GranularitySettings, GranularityThresholds, TextEngine};
+use crate::transactions::{self, TradeDraft, Transaction, TransactionRecord};
+use crate::win_probability::{self, WinProbabilityModel};
 use rand::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::ops::Range;
+use std::path::Path;
 
 const FIRST_NAMES: &str = include_str!("data/names/first_names.txt");
 const LAST_NAMES: &str = include_str!("data/names/last_names.txt");
 
+const OWN_TEAM_NAME: &str = "Montreal Expos";
+
+const ROTATION_SIZE: usize = 5;
+const BULLPEN_SIZE: usize = 8;
+const BENCH_SIZE: usize = 4;
+const FREE_AGENTS_PER_CLUB: usize = 4;
+const ROOKIES_PER_CLUB: usize = 3; // joining the free agent pool each offseason
+const LEADERS_PER_CATEGORY: usize = 5;
+
 fn get_first_names() -> Vec<&'static str> {
     FIRST_NAMES.lines().collect()
 }
@@ -18,7 +43,7 @@ fn get_last_names() -> Vec<&'static str> {
 fn generate_name(
     first_names: &[&'static str],
     last_names: &[&'static str],
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> String {
     let first_name = first_names.choose(rng).unwrap();
     let last_name = last_names.choose(rng).unwrap();
@@ -35,245 +60,1532 @@ fn generate_name(
     name
 }
 
-fn generate_players(num_players: usize, rng: &mut ThreadRng) -> HashMap<String, Player> {
-    let first_names = get_first_names();
-    let last_names = get_last_names();
+/// Generates the players that make up the league, giving each a new id and, to keep commentary readable, a name nobody else in the league has.
+#[derive(Debug)]
+struct PlayerGenerator {
+    first_names: Vec<&'static str>,
+    last_names: Vec<&'static str>,
+    names_in_use: HashSet<String>,
+    next_id: u32,
+    players: HashMap<PlayerId, Player>, // generated since the game last took them
+}
+
+impl PlayerGenerator {
+    fn new() -> Self {
+        Self {
+            first_names: get_first_names(),
+            last_names: get_last_names(),
+            names_in_use: HashSet::new(),
+            next_id: 0,
+            players: HashMap::new(),
+        }
+    }
 
-    let mut all_players = HashMap::new();
-    for _ in 0..num_players {
-        let name = generate_name(&first_names, &last_names, rng);
-        let player = Player {
-            name: name.clone(),
-            metrics: PlayerMetrics::random(rng),
+    /// Returns a name nobody in the league has. When the name drawn is taken, a number tells the new player apart, so a crowded league never has to keep drawing.
+    fn generate_unique_name(&mut self, rng: &mut GameRng) -> String {
+        let drawn = generate_name(&self.first_names, &self.last_names, rng);
+        let name = (1..)
+            .map(|n| if n == 1 { drawn.clone() } else { format!("{drawn} ({n})") })
+            .find(|name| !self.names_in_use.contains(name))
+            .unwrap();
+        self.names_in_use.insert(name.clone());
+        name
+    }
+
+    /// Frees the name of a player who has left the league for someone new.
+    fn free_name(&mut self, name: &str) {
+        self.names_in_use.remove(name);
+    }
+
+    fn generate_player(&mut self, position: Position, rng: &mut GameRng) -> PlayerId {
+        let id = PlayerId(self.next_id);
+        self.next_id += 1;
+        let mut player = Player {
+            id,
+            name: self.generate_unique_name(rng),
+            position,
+            age: rng.random_range(21..=37),
+            potential: 0.0,
+            contract: None,
+            metrics: PlayerMetrics::for_position(position, rng),
         };
-        all_players.insert(name, player);
+        player.potential = development::random_potential(&player, rng);
+        player.contract = Some(transactions::random_contract(&player, rng));
+        self.players.insert(id, player);
+
+        id
+    }
+
+    fn generate_players(&mut self, positions: &[Position], rng: &mut GameRng) -> Vec<PlayerId> {
+        positions.iter().map(|position| self.generate_player(*position, rng)).collect()
     }
 
-    all_players
+    /// Returns a `Team` with a full 26-man roster of newly generated players: a starter at every position, a designated hitter, a bench with a backup catcher, a starting rotation and a bullpen.
+    fn generate_team(&mut self, team_name: &str, rng: &mut GameRng) -> Team {
+        let fielders = self.generate_players(&Position::FIELDING, rng);
+        let designated_hitter = self.generate_player(*Position::FIELDING.choose(rng).unwrap(), rng);
+
+        let mut bench_positions = vec![Position::Catcher];
+        bench_positions.extend(Position::FIELDING.choose_multiple(rng, BENCH_SIZE - 1));
+        let bench = self.generate_players(&bench_positions, rng);
+
+        let all_pitchers = self.generate_players(&[Position::Pitcher; ROTATION_SIZE], rng);
+        let bullpen = self.generate_players(&[Position::Pitcher; BULLPEN_SIZE], rng);
+
+        let mut batting_order = fielders.to_vec();
+        batting_order.push(designated_hitter);
+        batting_order.shuffle(rng);
+
+        Team {
+            name: team_name.to_string(),
+            batting_order: batting_order.try_into().unwrap(),
+            current_pitcher: all_pitchers[0],
+            all_pitchers,
+            rotation_index: 0,
+            days_of_rest: HashMap::new(),
+            fielders: fielders.try_into().unwrap(),
+            bench,
+            bullpen,
+        }
+    }
+
+    /// Generates unsigned players at random positions, about as many pitchers as position players.
+    fn generate_free_agents(&mut self, num_players: usize, rng: &mut GameRng) -> Vec<PlayerId> {
+        (0..num_players).map(|_| {
+            let position = if rng.random_bool(0.5) { Position::Pitcher } else { *Position::FIELDING.choose(rng).unwrap() };
+            let id = self.generate_player(position, rng);
+            self.players.get_mut(&id).unwrap().contract = None;
+            id
+        }).collect()
+    }
+
+    /// Generates unsigned rookies: young, still raw, and with room to grow.
+    fn generate_rookies(&mut self, num_players: usize, rng: &mut GameRng) -> Vec<PlayerId> {
+        let rookies = self.generate_free_agents(num_players, rng);
+        for rookie in rookies.iter() {
+            let player = self.players.get_mut(rookie).unwrap();
+            player.age = development::rookie_age(rng);
+            player.metrics.nudge(player.position, false, rng);
+            player.potential = development::random_potential(player, rng);
+        }
+        rookies
+    }
 }
 
-/// Returns a `Team` with the given name and players taken from the given list of names, as well as a vector of the names consumed from the list.
-fn generate_team(team_name: &str, all_names: &mut Vec<String>) -> (Team, Vec<String>) {
-    let batting_order = all_names.drain(..9).collect::<Vec<_>>();
-    let all_pitchers = all_names.drain(..5).collect::<Vec<_>>();
-    let current_pitcher = all_pitchers[0].clone();
-    let fielders = all_names.drain(..8).collect::<Vec<_>>();
-    let bullpen = all_names.clone();
-
-    let team = Team {
-        name: team_name.to_string(),
-        batting_order: batting_order.clone().try_into().unwrap(),
-        all_pitchers: all_pitchers.clone(),
-        current_pitcher: current_pitcher,
-        fielders: fielders.clone().try_into().unwrap(),
-        bullpen: bullpen.clone(),
-    };
+/// Returns the given players in id order. Anything random done to each player in turn goes through here, so it happens in the same order every time a game is played from the same seed.
+fn in_id_order(players: &mut HashMap<PlayerId, Player>) -> Vec<&mut Player> {
+    let mut players = players.values_mut().collect::<Vec<_>>();
+    players.sort_by_key(|player| player.id);
+    players
+}
 
-    let mut consumed_names = Vec::new();
-    consumed_names.extend(batting_order);
-    consumed_names.extend(all_pitchers);
-    consumed_names.extend(fielders);
-    consumed_names.extend(bullpen);
+/// Returns the demon powers the expos can use in the given game right now: allowed here, affordable and off cooldown.
+fn usable_demon_powers(
+    favors: u32,
+    power_cooldowns: &HashMap<DemonPower, u32>,
+    current_game: &BaseballGame,
+    own_side: TeamSide,
+    granularity: Granularity,
+) -> Vec<DemonPower> {
+    demon::DEMON_POWERS.iter()
+        .filter(|spec| spec.favor_cost <= favors)
+        .filter(|spec| power_cooldowns.get(&spec.power).copied().unwrap_or(0) == 0)
+        .filter(|spec| spec.can_be_used(current_game, own_side, granularity))
+        .map(|spec| spec.power)
+        .collect()
+}
 
-    (team, consumed_names)
+/// Returns the relievers a side can still bring in: everyone in the bullpen who hasn't pitched in the game yet.
+fn available_relievers(current_game: &BaseballGame, box_score: &BoxScore, side: TeamSide) -> Vec<PlayerId> {
+    let pitched = &box_score.team_box(side).pitching;
+    current_game.team(side).bullpen.iter()
+        .filter(|reliever| !pitched.iter().any(|line| line.player == **reliever))
+        .copied()
+        .collect()
 }
 
-#[derive(Debug)]
+/// Makes any pitching change the computer-managed opponents want before the next pitch, returning a description of it.
+fn change_opponent_pitcher(current_game: &mut BaseballGame, box_score: &BoxScore, text_engine: &TextEngine, own_side: TeamSide) -> Option<String> {
+    let side = own_side.other();
+    let reliever = sim::ai_pitching_change(current_game.team(side), side, &current_game.state_summary(), box_score)?;
+    current_game.change_pitcher(side, reliever);
+    Some(text_engine.describe_pitching_change(side, reliever))
+}
+
+/// Records the pitch just thrown in the box score and returns the output describing it, remarking on the win probability when the pitch swung it a long way.
+fn pitch_output(
+    current_game: &BaseballGame,
+    box_score: &mut BoxScore,
+    text_engine: &TextEngine,
+    prev_game_state_summary: &GameStateSummary,
+    events_summary: EventsSummary,
+    win_probability: &WinProbabilityModel,
+) -> GameOutput {
+    let new_game_state_summary = current_game.state_summary();
+    box_score.record(prev_game_state_summary, &new_game_state_summary, &events_summary);
+    let mut description = text_engine.describe_pitch_level_summaries(prev_game_state_summary, &events_summary, &new_game_state_summary);
+
+    let prev_home_win_probability = win_probability.home_win_probability(prev_game_state_summary);
+    let home_win_probability = match current_game.state.game_outcome {
+        GameOutcome::HomeTeamWins => 1.0,
+        GameOutcome::VisitingTeamWins => 0.0,
+        GameOutcome::Ongoing => win_probability.home_win_probability(&new_game_state_summary),
+    };
+    if current_game.state.game_outcome == GameOutcome::Ongoing && (home_win_probability - prev_home_win_probability).abs() >= win_probability::BIG_SWING {
+        description = format!("{description} {}", text_engine.describe_win_probability_swing(prev_home_win_probability, home_win_probability));
+    }
+
+    GameOutput::PitchOutput {
+        events_summary,
+        new_game_state_summary,
+        home_win_probability,
+        description,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub enum UserInput {
-    // for now
+    // season inputs
     StartNewGame,
+    AdvanceToNextGame,
+    StartNextSeason, // once the postseason is over
+    ViewStandings,
+    ViewLeaders,
+
+    // pre-game inputs
+    ViewLineup,
+    SwapBattingOrder(usize, usize),
+    AssignFielder(Position, PlayerId),
+    SetDesignatedHitter(PlayerId),
+    ChooseStartingPitcher(PlayerId),
+    SkipStarter,
+    SwapRotation(usize, usize),
 
     // pitch-level inputs
     PitchAim(StrikeZoneLocation),
     BatterDecision(BatterDecision),
 
-    // inning-level inputs
+    // inputs for anything coarser than a pitch
     ContinuePlay,
+
+    // fielding inputs, while the expos are in the field
+    ChangePitcher(PlayerId), // to a reliever who hasn't pitched yet
+
+    // demon inputs, usable at any granularity
+    UseDemonPower(DemonPower),
+    SacrificeOut,
+
+    // between-games inputs
+    PerformRitual(Ritual),
+    ViewTransactions,
+    SignFreeAgent(PlayerId),
+    ReleasePlayer(PlayerId),
+    StartTradeTalks(String), // with the named team
+
+    // settings inputs, usable at any time
+    SetGranularityThresholds(GranularityThresholds),
+    ForceGranularity(Option<Granularity>), // None to go back to following the leverage
+
+    // trade inputs, while talks are open
+    OfferPlayer(PlayerId),
+    RequestPlayer(PlayerId),
+    WithdrawPlayer(PlayerId), // from either side of the trade
+    SubmitTrade,
+    CancelTrade,
+}
+
+/// A game the expos are due to play.
+#[derive(Debug, Clone, Copy)]
+enum Fixture {
+    RegularSeason(GameSlot),
+    Postseason(SeriesSlot),
 }
 
 #[derive(Debug)]
 enum GamePhase {
     PreGame,
     InGame {
-        baseball_game: BaseballGame,
+        baseball_game: Box<BaseballGame>,
         text_engine: TextEngine,
+        box_score: Box<BoxScore>,
+        own_side: TeamSide, // the side the expos are playing on
+        fixture: Fixture,
+    },
+    BetweenGames {
+        has_prayed: bool,
+        trade_draft: Option<TradeDraft>, // Some while the expos are in trade talks
     },
-    BetweenGames,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum GameOutput {
     PitchOutput {
         events_summary: EventsSummary,
         new_game_state_summary: GameStateSummary,
+        home_win_probability: f64, // after the pitch
         description: String,
     },
-    HalfInningOutput {
+    PlayOutput {
+        granularity: Granularity, // what the stretch of play covered, unless it got tense enough to stop early
         events_summaries: Vec<EventsSummary>,
         game_state_summaries: Vec<GameStateSummary>,
         description: String,
     },
+    DemonPowerOutput {
+        power: DemonPower,
+        backfired: bool,
+        favors: u32,
+        description: String,
+    },
+    SacrificeOutOutput {
+        new_game_state_summary: GameStateSummary,
+        favors: u32,
+        description: String,
+    },
+    PitchingChangeOutput {
+        pitcher: PlayerId,
+        description: String,
+    },
+    RitualOutput {
+        ritual: Ritual,
+        goodwill: f64,
+        description: String,
+    },
     StartNewGame,
+    GameOver {
+        final_play: Box<GameOutput>, // what the input that ended the game would otherwise have returned
+        result: GameResult,
+        box_score: BoxScore,
+        history: Vec<GameEvent>, // every play of the game, oldest first, for replays
+        description: String,
+    },
+    NextGame {
+        home_team: String,
+        visiting_team: String,
+        opponent_starter: PlayerId,
+        description: String,
+    },
+    Lineup {
+        batting_order: [PlayerId; 9],
+        fielders: [PlayerId; 8],
+        starting_pitcher: PlayerId,
+        opponent_starter: PlayerId,
+        description: String,
+    },
+    PostseasonOver {
+        champion: String,
+        description: String,
+    },
+    NewSeason {
+        season: u32,
+        awards: Vec<Award>, // for the season just finished
+        retired: Vec<String>, // names, since the players have left the league
+        description: String,
+    },
+    Standings {
+        standings: Vec<DivisionStandings>,
+        description: String,
+    },
+    Leaders {
+        leaders: Vec<(StatCategory, Vec<(PlayerId, f64)>)>, // this season, best first
+        description: String,
+    },
+    TradeTalks {
+        team: String,
+        offered: Vec<PlayerId>,
+        requested: Vec<PlayerId>,
+        description: String,
+    },
+    TradeResponse {
+        accepted: bool,
+        description: String,
+    },
+    TransactionOutput {
+        transaction: Transaction,
+        description: String,
+    },
+    Transactions {
+        records: Vec<TransactionRecord>,
+        description: String,
+    },
+    GranularityOutput {
+        settings: GranularitySettings,
+        description: String,
+    },
 }
 
 #[derive(Debug)]
 pub enum GameError {
     InvalidUserInput,
+    InvalidLineup(LineupError),
+    SeasonOver,
+    EventLog(io::Error), // the input went through, but couldn't be written to the event log
+}
+
+/// Returns the side in the field as things stand in the given state.
+fn fielding_side(game_state_summary: &GameStateSummary) -> TeamSide {
+    if game_state_summary.half_inning.top { TeamSide::Home } else { TeamSide::Visiting }
+}
+
+/// Returns how the game stood before the given event, for the events that carry it.
+fn state_before(event: &GameEvent) -> Option<&GameStateSummary> {
+    match event {
+        GameEvent::Pitch { prev_game_state_summary, .. } | GameEvent::SacrificedOut { prev_game_state_summary, .. } => Some(prev_game_state_summary),
+        GameEvent::PitchingChange { .. } => None,
+    }
+}
+
+/// A finished game laid out play by play, to be stepped through forwards and backwards with commentary.
+#[derive(Debug)]
+pub struct Replay {
+    history: Vec<GameEvent>,
+    states: Vec<GameStateSummary>, // states[i] is how the game stood after the first i plays
+    position: usize, // how many plays have been stepped through
+    text_engine: TextEngine,
+}
+
+impl Replay {
+    /// Lays out the given history for commentary from the given text engine, starting before the first play. Returns None if the history has no pitches in it.
+    pub fn new(history: Vec<GameEvent>, text_engine: TextEngine) -> Option<Self> {
+        let first_pitch = history.iter().position(|event| state_before(event).is_some())?;
+        let mut state = state_before(&history[first_pitch]).unwrap().clone();
+        // the state before the first pitch already has any pitcher brought in before it
+        for event in history[..first_pitch].iter().rev() {
+            if let GameEvent::PitchingChange { side, replaced, .. } = event && fielding_side(&state) == *side {
+                state.pitcher = *replaced;
+            }
+        }
+
+        let mut states = vec![state];
+        for event in history.iter() {
+            let state = match event {
+                GameEvent::Pitch { new_game_state_summary, .. } | GameEvent::SacrificedOut { new_game_state_summary, .. } => new_game_state_summary.clone(),
+                GameEvent::PitchingChange { side, pitcher, .. } => {
+                    let mut state = states.last().unwrap().clone();
+                    if fielding_side(&state) == *side {
+                        state.pitcher = *pitcher;
+                    }
+                    state
+                },
+            };
+            states.push(state);
+        }
+
+        Some(Self { history, states, position: 0, text_engine })
+    }
+
+    /// Returns the number of plays in the game, pitching changes included.
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    /// Returns how many plays have been stepped through so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns how the game stands at the current position.
+    pub fn state(&self) -> &GameStateSummary {
+        &self.states[self.position]
+    }
+
+    /// Returns how the game stood before the first play and after each one, in order.
+    pub fn states(&self) -> &[GameStateSummary] {
+        &self.states
+    }
+
+    /// Steps over the next play and returns commentary on it, or None if the game is already over.
+    pub fn step_forward(&mut self) -> Option<String> {
+        if self.position == self.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.describe_play(self.position - 1))
+    }
+
+    /// Steps back before the last play and returns commentary on the play undone, or None if already at the start.
+    pub fn step_back(&mut self) -> Option<String> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.describe_play(self.position))
+    }
+
+    /// Moves straight to the given position, clamped to the end of the game.
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.len());
+    }
+
+    /// Returns commentary on the play at the given index, counting from 0.
+    pub fn describe_play(&self, index: usize) -> String {
+        match &self.history[index] {
+            GameEvent::Pitch { prev_game_state_summary, events_summary, new_game_state_summary } => {
+                self.text_engine.describe_pitch_level_summaries(prev_game_state_summary, events_summary, new_game_state_summary)
+            },
+            GameEvent::SacrificedOut { prev_game_state_summary, new_game_state_summary } => {
+                self.text_engine.describe_sacrificed_out(prev_game_state_summary, new_game_state_summary)
+            },
+            GameEvent::PitchingChange { side, pitcher, .. } => self.text_engine.describe_pitching_change(*side, *pitcher),
+        }
+    }
+
+    /// Swaps in a different text engine, so every play is described afresh from here on.
+    pub fn set_text_engine(&mut self, text_engine: TextEngine) {
+        self.text_engine = text_engine;
+    }
 }
 
 #[derive(Debug)]
 pub struct Game {
-    all_players: HashMap<String, Player>,
+    all_players: HashMap<PlayerId, Player>,
     own_team: Option<Team>, // will be None until the user sets it up, then it will be Some for the rest of the game
     other_teams: HashMap<String, Team>,
-    rng: ThreadRng,
+    league: LeagueStructure,
+    free_agents: HashMap<PlayerId, Player>, // unsigned players, used to fill roster spots left by the dead
+    goodwill: f64, // rorzagoth's goodwill towards the expos, between 0 and 1
+    favors: u32, // owed by rorzagoth, spent on demon powers
+    power_cooldowns: HashMap<DemonPower, u32>, // pitches until each power can be used again
+    season: Season,
+    transactions: Vec<TransactionRecord>, // every roster move in the league, oldest first
+    postseason: Option<Postseason>, // Some once the regular season is over
+    postseason_config: PostseasonConfig,
+    season_number: u32, // counting from 1
+    stats: StatBook,
+    player_generator: PlayerGenerator, // kept for the rookies who join the league each offseason
+    event_log: Option<EventLog>, // Some once the user asks for play to be logged
+    granularity_settings: GranularitySettings,
+    win_probability: WinProbabilityModel, // major league play unless calibrated from simulated games
+    seed: u64, // what the rng started from, so the game can be played again from the same inputs
+    rng: GameRng,
     phase: GamePhase,
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Game {
     pub fn new() -> Self {
-        let mut rng = rand::rng();
+        Self::with_league(LeagueStructure::default())
+    }
 
-        let all_possible_players = generate_players(1000, &mut rng);
-        let mut all_possible_names = all_possible_players.keys().cloned().collect::<Vec<_>>();
+    /// Creates a game in the given league, which must include the expos. Every other club gets a generated team.
+    pub fn with_league(league: LeagueStructure) -> Self {
+        Self::with_seed(league, rand::random())
+    }
+
+    /// Creates a game in the given league with the rng started from the given seed. Two games made from the same league and seed, then given the same inputs, play out exactly the same. The season and postseason are made to fit the league.
+    pub fn with_seed(league: LeagueStructure, seed: u64) -> Self {
+        let season_config = SeasonConfig::for_league(&league);
+        let postseason_config = PostseasonConfig::for_league(&league);
+        Self::with_config(league, season_config, postseason_config, seed)
+    }
 
-        let mut all_players = HashMap::new();
-        let (own_team, consumed_names) = generate_team("Montreal Expos", &mut all_possible_names);
-        let players: HashMap<String, Player> = consumed_names.iter()
-            .map(|name| (name.clone(), all_possible_players.get(name).unwrap().clone()))
-            .collect();
-        all_players.extend(players);
+    /// Creates a game in the given league with the given season and postseason formats and the rng started from the given seed. The season config must fit the league.
+    pub fn with_config(league: LeagueStructure, season_config: SeasonConfig, postseason_config: PostseasonConfig, seed: u64) -> Self {
+        assert!(league.club(OWN_TEAM_NAME).is_some(), "the league must include the {OWN_TEAM_NAME}");
+        let mut rng = GameRng::seed_from_u64(seed);
 
+        let mut player_generator = PlayerGenerator::new();
+        let mut own_team = None;
         let mut other_teams = HashMap::new();
-        let other_team_names = [
-            "New York Yankees",
-            "Chicago Cubs",
-            "Los Angeles Dodgers",
-        ];
-        for team_name in other_team_names {
-            let (team, consumed_names) = generate_team(team_name, &mut all_possible_names);
-            other_teams.insert(team_name.to_string(), team);
-
-            let players: HashMap<String, Player> = consumed_names.iter()
-                .map(|name| (name.clone(), all_possible_players.get(name).unwrap().clone()))
-                .collect();
-            all_players.extend(players);
+        for team_name in league.team_names() {
+            let team = player_generator.generate_team(&team_name, &mut rng);
+            if team_name == OWN_TEAM_NAME {
+                own_team = Some(team);
+            } else {
+                other_teams.insert(team_name, team);
+            }
         }
+        let all_players = std::mem::take(&mut player_generator.players);
+
+        player_generator.generate_free_agents(league.clubs.len() * FREE_AGENTS_PER_CLUB, &mut rng);
+        let free_agents = std::mem::take(&mut player_generator.players);
+
+        let season = Season::new(&league, season_config, &mut rng);
 
         Self {
             all_players,
-            own_team: Some(own_team),
+            own_team,
             other_teams,
+            league,
+            free_agents,
+            goodwill: demon::STARTING_GOODWILL,
+            favors: demon::STARTING_FAVORS,
+            power_cooldowns: HashMap::new(),
+            season,
+            transactions: Vec::new(),
+            postseason: None,
+            postseason_config,
+            season_number: 1,
+            stats: StatBook::new(seed),
+            player_generator,
+            event_log: None,
+            granularity_settings: GranularitySettings::default(),
+            win_probability: WinProbabilityModel::default(),
+            seed,
             rng,
             phase: GamePhase::PreGame,
         }
     }
 
-    pub fn start_new_game(&mut self) {
-        let visiting_team = self.other_teams.values().choose(&mut self.rng).unwrap().clone();
-        let baseball_game = BaseballGame::new(
+    /// Returns the seed the game's rng started from. Along with the league and every input given, it's all it takes to play the game again.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn team_names(&self) -> Vec<String> {
+        self.league.team_names()
+    }
+
+    fn team_mut(&mut self, team_name: &str) -> &mut Team {
+        let own_team = self.own_team.as_mut().unwrap();
+        if own_team.name == team_name {
+            own_team
+        } else {
+            self.other_teams.get_mut(team_name).unwrap()
+        }
+    }
+
+    /// Returns the name of any player on a roster or in the free agent pool.
+    pub fn player_name(&self, player: PlayerId) -> &str {
+        let player = self.all_players.get(&player).or_else(|| self.free_agents.get(&player)).unwrap();
+        &player.name
+    }
+
+    fn team(&self, team_name: &str) -> &Team {
+        let own_team = self.own_team.as_ref().unwrap();
+        if own_team.name == team_name {
+            own_team
+        } else {
+            self.other_teams.get(team_name).unwrap()
+        }
+    }
+
+    /// Returns where the expos' next unplayed game is on the schedule, if they have any left.
+    fn next_own_game(&self) -> Option<GameSlot> {
+        let own_team_name = &self.own_team.as_ref().unwrap().name;
+        self.season.schedule.next_game_for(own_team_name, self.season.current_day)
+    }
+
+    /// Returns the expos' next game, in the regular season or the postseason, if they have one left.
+    fn next_own_fixture(&self) -> Option<Fixture> {
+        match &self.postseason {
+            Some(postseason) => postseason.series_for(&self.own_team.as_ref().unwrap().name).map(Fixture::Postseason),
+            None => self.next_own_game().map(Fixture::RegularSeason),
+        }
+    }
+
+    /// Returns the home and visiting teams of the given fixture.
+    fn fixture_teams(&self, fixture: Fixture) -> (String, String) {
+        match fixture {
+            Fixture::RegularSeason(slot) => {
+                let scheduled_game = self.season.schedule.game(slot);
+                (scheduled_game.home_team.clone(), scheduled_game.visiting_team.clone())
+            },
+            Fixture::Postseason(slot) => self.postseason.as_ref().unwrap().series(slot).next_game().unwrap(),
+        }
+    }
+
+    pub fn standings(&self) -> Vec<StandingsRow> {
+        self.season.standings(&self.team_names())
+    }
+
+    /// Plays a whole game between two teams in the background, with each starting whoever their rotation calls for.
+    fn simulate_game(&mut self, home_team_name: &str, visiting_team_name: &str) -> SimulatedGame {
+        self.team_mut(home_team_name).choose_starter();
+        self.team_mut(visiting_team_name).choose_starter();
+        let simulated_game = sim::simulate_game(
             self.all_players.clone(),
-            self.own_team.as_ref().unwrap().clone(),
-            visiting_team.clone(),
+            self.team(home_team_name).clone(),
+            self.team(visiting_team_name).clone(),
+            &mut self.rng,
         );
-        let text_engine = TextEngine::new(
+        self.team_mut(home_team_name).record_start();
+        self.team_mut(visiting_team_name).record_start();
+
+        simulated_game
+    }
+
+    fn rest_all_teams(&mut self) {
+        self.own_team.as_mut().unwrap().pass_day();
+        for team in self.other_teams.values_mut() {
+            team.pass_day();
+        }
+    }
+
+    /// Gives every player's ratings their daily chance to drift.
+    fn drift_all_players(&mut self) {
+        for player in in_id_order(&mut self.all_players).into_iter().chain(in_id_order(&mut self.free_agents)) {
+            development::drift(player, &mut self.rng);
+        }
+    }
+
+    /// Finishes the current day and every day after it up to the given one, playing the rest of the league's games and resting every pitcher a day at a time.
+    fn advance_calendar(&mut self, to_day: usize) {
+        while self.season.current_day < to_day {
+            let day = self.season.current_day;
+            self.play_other_games(day..day + 1);
+            self.rest_all_teams();
+            self.drift_all_players();
+            self.season.current_day += 1;
+        }
+    }
+
+    /// Plays every unplayed game on the given days that doesn't involve the expos.
+    fn play_other_games(&mut self, days: Range<usize>) {
+        let own_team_name = self.own_team.as_ref().unwrap().name.clone();
+        for day in days {
+            for game in 0..self.season.schedule.days[day].games.len() {
+                let slot = GameSlot { day, game };
+                let scheduled_game = self.season.schedule.game(slot);
+                if scheduled_game.result.is_none() && !scheduled_game.involves(&own_team_name) {
+                    let (home_team_name, visiting_team_name) = self.fixture_teams(Fixture::RegularSeason(slot));
+                    let simulated_game = self.simulate_game(&home_team_name, &visiting_team_name);
+                    self.season.record_result(slot, simulated_game.result);
+                    self.stats.record_game(&simulated_game.box_score, &simulated_game.history, &self.all_players);
+                }
+            }
+        }
+    }
+
+    fn start_postseason(&mut self) {
+        self.advance_calendar(self.season.schedule.days.len());
+        self.postseason = Some(Postseason::new(&self.standings(), &self.league, self.postseason_config.clone()));
+    }
+
+    /// Plays every postseason series that doesn't involve the expos, round by round, until the expos have a game to play or a champion is crowned.
+    fn advance_postseason(&mut self) {
+        let own_team_name = self.own_team.as_ref().unwrap().name.clone();
+
+        loop {
+            let postseason = self.postseason.as_ref().unwrap();
+            let other_series = postseason.undecided_series().into_iter()
+                .filter(|slot| !postseason.series(*slot).involves(&own_team_name))
+                .collect::<Vec<_>>();
+            for slot in other_series {
+                while let Some((home_team_name, visiting_team_name)) = self.postseason.as_ref().unwrap().series(slot).next_game() {
+                    self.team_mut(&home_team_name).pass_day();
+                    self.team_mut(&visiting_team_name).pass_day();
+                    let result = self.simulate_game(&home_team_name, &visiting_team_name).result;
+                    self.postseason.as_mut().unwrap().series_mut(slot).record_game(home_team_name, visiting_team_name, result);
+                }
+            }
+
+            let postseason = self.postseason.as_mut().unwrap();
+            if postseason.series_for(&own_team_name).is_some() || !postseason.advance_round() {
+                break;
+            }
+        }
+    }
+
+    fn season_is_finished(&self) -> bool {
+        self.postseason.as_ref().is_some_and(|postseason| postseason.champion().is_some())
+    }
+
+    /// Moves on from a finished season to the next one: everyone ages a year and develops, contracts run down, rookies join the free agent pool, retiring players are replaced and a new schedule is drawn up.
+    fn start_next_season(&mut self) -> GameOutput {
+        let awards = self.stats.award_winners(&self.league).into_iter()
+            .map(|(kind, league, player)| Award {
+                season: self.season_number,
+                kind,
+                league: league.clone(),
+                player,
+                description: text::describe_award(kind, &league, self.stats.season.get(&player).unwrap()),
+            })
+            .collect::<Vec<_>>();
+        self.stats.awards.extend(awards.iter().cloned());
+        self.stats.start_new_season();
+
+        self.season_number += 1;
+        self.season = Season::new(&self.league, self.season.config.clone(), &mut self.rng);
+        self.postseason = None;
+
+        for player in in_id_order(&mut self.all_players).into_iter().chain(in_id_order(&mut self.free_agents)) {
+            development::develop_over_offseason(player, &mut self.rng);
+        }
+        // expiring contracts are renewed at the player's new asking salary
+        for player in in_id_order(&mut self.all_players) {
+            let contract = player.contract.unwrap();
+            player.contract = Some(if contract.years > 1 {
+                Contract { years: contract.years - 1, ..contract }
+            } else {
+                transactions::random_contract(player, &mut self.rng)
+            });
+        }
+
+        self.player_generator.generate_rookies(self.league.clubs.len() * ROOKIES_PER_CLUB, &mut self.rng);
+        let rookies = std::mem::take(&mut self.player_generator.players);
+        let num_rookies = rookies.len();
+        let retiring_free_agents = in_id_order(&mut self.free_agents).into_iter()
+            .filter(|player| development::retires(player, &mut self.rng))
+            .map(|player| player.id)
+            .collect::<Vec<_>>();
+        for player in retiring_free_agents {
+            let player = self.free_agents.remove(&player).unwrap();
+            self.player_generator.free_name(&player.name);
+        }
+        self.free_agents.extend(rookies);
+
+        let mut retired = Vec::new();
+        for team_name in self.team_names() {
+            for player in self.team(&team_name).roster() {
+                if development::retires(self.all_players.get(&player).unwrap(), &mut self.rng) {
+                    retired.push(self.player_name(player).to_string());
+                    self.record_transaction(Transaction::Retirement { team: team_name.clone(), player });
+                    self.sign_replacement(&team_name, player);
+                    let player = self.all_players.remove(&player).unwrap();
+                    self.player_generator.free_name(&player.name);
+                }
+            }
+
+            let team = self.team_mut(&team_name);
+            team.days_of_rest.clear();
+            team.rotation_index = 0;
+        }
+
+        self.phase = GamePhase::BetweenGames { has_prayed: false, trade_draft: None };
+        let mut description = awards.iter().map(|award| award.description.clone()).collect::<Vec<_>>();
+        description.push(text::describe_new_season(self.season_number, &retired.iter().map(String::as_str).collect::<Vec<_>>(), num_rookies));
+
+        GameOutput::NewSeason { season: self.season_number, awards, retired, description: description.join(" ") }
+    }
+
+    /// Returns the name of the expos' next opponent, if they have another game.
+    fn next_opponent(&self) -> Option<String> {
+        let (home_team_name, visiting_team_name) = self.fixture_teams(self.next_own_fixture()?);
+        if home_team_name == self.own_team.as_ref().unwrap().name {
+            Some(visiting_team_name)
+        } else {
+            Some(home_team_name)
+        }
+    }
+
+    /// Returns the pitcher the given team is expected to start in their next game.
+    fn probable_starter(&self, team_name: &str) -> PlayerId {
+        self.team(team_name).current_pitcher
+    }
+
+    fn lineup_output(&self) -> Result<GameOutput, GameError> {
+        let own_team = self.own_team.as_ref().unwrap();
+        let opponent = self.next_opponent().ok_or(GameError::SeasonOver)?;
+        let opponent_starter = self.probable_starter(&opponent);
+        let description = text::describe_lineup(own_team, &self.all_players, &opponent, self.player_name(opponent_starter));
+
+        Ok(GameOutput::Lineup {
+            batting_order: own_team.batting_order,
+            fielders: own_team.fielders,
+            starting_pitcher: own_team.current_pitcher,
+            opponent_starter,
+            description,
+        })
+    }
+
+    /// Returns every lineup change the expos can make with their current roster.
+    fn lineup_inputs(&self) -> Vec<UserInput> {
+        let own_team = self.own_team.as_ref().unwrap();
+        let mut user_inputs = Vec::new();
+        for a in 0..own_team.batting_order.len() {
+            for b in a + 1..own_team.batting_order.len() {
+                user_inputs.push(UserInput::SwapBattingOrder(a, b));
+            }
+        }
+        let position_players = own_team.batting_order.iter().chain(own_team.bench.iter()).collect::<Vec<_>>();
+        for (position, fielder) in Position::FIELDING.iter().zip(own_team.fielders.iter()) {
+            for player in position_players.iter().filter(|player| **player != fielder) {
+                user_inputs.push(UserInput::AssignFielder(*position, **player));
+            }
+        }
+        if own_team.designated_hitter().is_some() {
+            for player in own_team.bench.iter() {
+                user_inputs.push(UserInput::SetDesignatedHitter(*player));
+            }
+        }
+        for pitcher in own_team.all_pitchers.iter().filter(|pitcher| **pitcher != own_team.current_pitcher) {
+            user_inputs.push(UserInput::ChooseStartingPitcher(*pitcher));
+        }
+        user_inputs.push(UserInput::SkipStarter);
+        for a in 0..own_team.all_pitchers.len() {
+            for b in a + 1..own_team.all_pitchers.len() {
+                user_inputs.push(UserInput::SwapRotation(a, b));
+            }
+        }
+        user_inputs
+    }
+
+    /// Starts the expos' next game.
+    pub fn start_new_game(&mut self) -> Result<(), GameError> {
+        let fixture = self.next_own_fixture().ok_or(GameError::SeasonOver)?;
+        self.own_team.as_ref().unwrap().validate_lineup(&self.all_players).map_err(GameError::InvalidLineup)?;
+        let (home_team_name, visiting_team_name) = self.fixture_teams(fixture);
+        let own_side = if home_team_name == self.own_team.as_ref().unwrap().name {
+            TeamSide::Home
+        } else {
+            TeamSide::Visiting
+        };
+        let home_team = self.team(&home_team_name).clone();
+        let visiting_team = self.team(&visiting_team_name).clone();
+        if let Fixture::RegularSeason(slot) = fixture {
+            self.season.current_day = slot.day;
+        }
+
+        let box_score = BoxScore::new(&home_team, &visiting_team);
+        let text_engine = self.text_engine(&home_team.name, &visiting_team.name);
+        let mut baseball_game = BaseballGame::new(
             self.all_players.clone(),
-            self.own_team.as_ref().unwrap().name.clone(),
-            visiting_team.name,
+            home_team,
+            visiting_team,
+            GameRng::from_rng(&mut self.rng),
         );
-        self.phase = GamePhase::InGame { baseball_game, text_engine };
+        if own_side == TeamSide::Home {
+            baseball_game.set_domain(Domain { side: own_side, goodwill: self.goodwill });
+        }
+        self.power_cooldowns.clear();
+        self.phase = GamePhase::InGame { baseball_game: Box::new(baseball_game), text_engine, box_score: Box::new(box_score), own_side, fixture };
+
+        Ok(())
+    }
+
+    /// Records the result of the given fixture and moves on to the between-games phase.
+    fn finish_game(&mut self, fixture: Fixture, result: GameResult) {
+        let (home_team_name, visiting_team_name) = self.fixture_teams(fixture);
+        self.team_mut(&home_team_name).record_start();
+        self.team_mut(&visiting_team_name).record_start();
+
+        match fixture {
+            Fixture::RegularSeason(slot) => {
+                self.season.record_result(slot, result);
+                self.play_other_games(slot.day..slot.day + 1);
+            },
+            Fixture::Postseason(slot) => {
+                self.postseason.as_mut().unwrap().series_mut(slot).record_game(home_team_name, visiting_team_name, result);
+            },
+        }
+        self.phase = GamePhase::BetweenGames { has_prayed: false, trade_draft: None };
+    }
+
+    /// Finishes the game that just ended and wraps the output of its final play with the result, box score and history.
+    fn game_over_output(&mut self, fixture: Fixture, result: GameResult, box_score: BoxScore, history: Vec<GameEvent>, final_play: GameOutput) -> GameOutput {
+        let description = text::describe_box_score(&box_score, &self.all_players);
+        if let Fixture::RegularSeason(_) = fixture {
+            self.stats.record_game(&box_score, &history, &self.all_players);
+        }
+        self.finish_game(fixture, result);
+
+        GameOutput::GameOver { final_play: Box::new(final_play), result, box_score, history, description }
+    }
+
+    /// Moves on to the expos' next game, starting and playing through the postseason as needed.
+    fn advance_to_next_game(&mut self) -> Result<GameOutput, GameError> {
+        if self.season_is_finished() {
+            return Err(GameError::SeasonOver);
+        }
+        if self.postseason.is_none() && self.next_own_game().is_none() {
+            self.start_postseason();
+        }
+        if self.postseason.is_some() {
+            self.advance_postseason();
+        }
+
+        let Some(fixture) = self.next_own_fixture() else {
+            let postseason = self.postseason.as_ref().unwrap();
+            return Ok(GameOutput::PostseasonOver {
+                champion: postseason.champion().unwrap().to_string(),
+                description: text::describe_postseason_over(postseason, &self.own_team.as_ref().unwrap().name),
+            });
+        };
+
+        let (home_team, visiting_team) = self.fixture_teams(fixture);
+        let description = match fixture {
+            Fixture::RegularSeason(slot) => {
+                self.advance_calendar(slot.day);
+                text::describe_next_game(slot.day, &home_team, &visiting_team)
+            },
+            Fixture::Postseason(slot) => {
+                self.team_mut(&home_team).pass_day();
+                self.team_mut(&visiting_team).pass_day();
+                let postseason = self.postseason.as_ref().unwrap();
+                text::describe_postseason_game(postseason.round_name(slot.round), postseason.series(slot), &home_team, &visiting_team)
+            },
+        };
+        self.phase = GamePhase::PreGame;
+        self.team_mut(&home_team).choose_starter();
+        self.team_mut(&visiting_team).choose_starter();
+
+        let opponent = if home_team == self.own_team.as_ref().unwrap().name { &visiting_team } else { &home_team };
+        let opponent_starter = self.probable_starter(opponent);
+        let description = format!("{description} {}", text::describe_probable_starter(opponent, self.player_name(opponent_starter)));
+
+        Ok(GameOutput::NextGame { home_team, visiting_team, opponent_starter, description })
+    }
+
+    /// Kills the given player, removes them from every roster and signs a free agent to take their place on the expos. Returns the replacement.
+    fn sacrifice_player(&mut self, victim: PlayerId) -> PlayerId {
+        let own_team_name = self.own_team.as_ref().unwrap().name.clone();
+        let replacement = self.sign_replacement(&own_team_name, victim);
+        let victim = self.all_players.remove(&victim).unwrap();
+        self.player_generator.free_name(&victim.name);
+
+        replacement
+    }
+
+    /// Signs a free agent to take the given player's place on the given team, in every slot they fill. Returns the replacement.
+    fn sign_replacement(&mut self, team_name: &str, player: PlayerId) -> PlayerId {
+        // replace like for like where the free agent pool allows it
+        let position = self.all_players.get(&player).unwrap().position;
+        let free_agents = in_id_order(&mut self.free_agents).into_iter().map(|player| (player.id, player.position)).collect::<Vec<_>>();
+        let same_position = free_agents.iter()
+            .filter(|(_, free_agent_position)| *free_agent_position == position)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        let replacement_id = same_position.choose(&mut self.rng)
+            .copied()
+            .unwrap_or_else(|| free_agents.choose(&mut self.rng).unwrap().0);
+        let mut replacement = self.free_agents.remove(&replacement_id).unwrap();
+        let contract = transactions::random_contract(&replacement, &mut self.rng);
+        replacement.contract = Some(contract);
+        self.all_players.insert(replacement_id, replacement);
+
+        self.team_mut(team_name).replace_player(player, replacement_id);
+        self.record_transaction(Transaction::Signing { team: team_name.to_string(), player: replacement_id, contract });
+
+        replacement_id
+    }
+
+    fn record_transaction(&mut self, transaction: Transaction) {
+        let description = match &transaction {
+            Transaction::Trade { team, other_team, sent, received } => text::describe_trade(
+                team,
+                other_team,
+                &sent.iter().map(|player| self.player_name(*player)).collect::<Vec<_>>(),
+                &received.iter().map(|player| self.player_name(*player)).collect::<Vec<_>>(),
+            ),
+            Transaction::Signing { team, player, contract } => text::describe_signing(team, self.player_name(*player), contract),
+            Transaction::Release { team, player } => text::describe_release(team, self.player_name(*player)),
+            Transaction::Retirement { team, player } => text::describe_retirement(team, self.player_name(*player), self.all_players.get(player).unwrap().age),
+        };
+        self.transactions.push(TransactionRecord { season: self.season_number, day: self.season.current_day, transaction, description });
+    }
+
+    /// Signs a free agent to the expos at their asking salary, on the bench or in the bullpen.
+    fn sign_free_agent(&mut self, player_id: PlayerId) -> GameOutput {
+        let mut player = self.free_agents.remove(&player_id).unwrap();
+        let contract = transactions::random_contract(&player, &mut self.rng);
+        player.contract = Some(contract);
+
+        let own_team = self.own_team.as_mut().unwrap();
+        if player.position == Position::Pitcher {
+            own_team.bullpen.push(player_id);
+        } else {
+            own_team.bench.push(player_id);
+        }
+        self.all_players.insert(player_id, player);
+
+        let transaction = Transaction::Signing { team: own_team.name.clone(), player: player_id, contract };
+        self.record_transaction(transaction.clone());
+
+        GameOutput::TransactionOutput { transaction, description: self.transactions.last().unwrap().description.clone() }
+    }
+
+    /// Releases a player from the expos' bench or bullpen into the free agent pool.
+    fn release_player(&mut self, player_id: PlayerId) -> GameOutput {
+        let transaction = Transaction::Release { team: self.own_team.as_ref().unwrap().name.clone(), player: player_id };
+        self.record_transaction(transaction.clone());
+
+        let own_team = self.own_team.as_mut().unwrap();
+        own_team.bench.retain(|player| *player != player_id);
+        own_team.bullpen.retain(|player| *player != player_id);
+        let mut player = self.all_players.remove(&player_id).unwrap();
+        player.contract = None;
+        self.free_agents.insert(player_id, player);
+
+        GameOutput::TransactionOutput { transaction, description: self.transactions.last().unwrap().description.clone() }
+    }
+
+    fn trade_talks_output(&self, trade_draft: &TradeDraft) -> GameOutput {
+        let names = |players: &[PlayerId]| players.iter().map(|player| self.player_name(*player)).collect::<Vec<_>>();
+        let description = text::describe_trade_draft(&trade_draft.team, &names(&trade_draft.offered), &names(&trade_draft.requested));
+
+        GameOutput::TradeTalks {
+            team: trade_draft.team.clone(),
+            offered: trade_draft.offered.clone(),
+            requested: trade_draft.requested.clone(),
+            description,
+        }
+    }
+
+    /// Puts the drafted trade to the other team. If they accept, every player swaps places on the roster with the player they were paired with.
+    fn submit_trade(&mut self, trade_draft: &TradeDraft) -> Result<bool, GameError> {
+        let pairings = trade_draft.pairings(&self.all_players).ok_or(GameError::InvalidUserInput)?;
+        if !transactions::ai_accepts_trade(&trade_draft.requested, &trade_draft.offered, &self.all_players) {
+            return Ok(false);
+        }
+
+        for (sent, received) in pairings {
+            self.own_team.as_mut().unwrap().replace_player(sent, received);
+            self.team_mut(&trade_draft.team).replace_player(received, sent);
+        }
+        self.record_transaction(Transaction::Trade {
+            team: self.own_team.as_ref().unwrap().name.clone(),
+            other_team: trade_draft.team.clone(),
+            sent: trade_draft.offered.clone(),
+            received: trade_draft.requested.clone(),
+        });
+
+        Ok(true)
+    }
+
+    fn set_trade_draft(&mut self, draft: Option<TradeDraft>) {
+        if let GamePhase::BetweenGames { trade_draft, .. } = &mut self.phase {
+            *trade_draft = draft;
+        }
+    }
+
+    fn transactions_output(&self) -> GameOutput {
+        GameOutput::Transactions {
+            records: self.transactions.clone(),
+            description: text::describe_transactions(&self.transactions),
+        }
+    }
+
+    fn leaders_output(&self) -> GameOutput {
+        let leaders = StatCategory::ALL.iter()
+            .map(|category| (*category, self.stats.leaders(*category, StatScope::Season, LEADERS_PER_CATEGORY)))
+            .collect::<Vec<_>>();
+        let description = text::describe_leaders(&leaders, &self.stats.season);
+
+        GameOutput::Leaders { leaders, description }
+    }
+
+    /// Writes every player's season and career stats, and the awards handed out so far, to the given file.
+    pub fn save_stats(&self, path: &Path) -> io::Result<()> {
+        self.stats.save(path)
+    }
+
+    /// Starts appending every input and its output to the event log at the given path.
+    pub fn start_event_log(&mut self, path: &Path) -> io::Result<()> {
+        self.event_log = Some(EventLog::open(path)?);
+        Ok(())
+    }
+
+    /// Plays a batch of games between the league's teams as they stand, without touching the season, and returns league-wide totals. See `batch::simulate_batch`.
+    pub fn simulate_batch(&self, games: u32, workers: usize, seed: u64) -> BatchTotals {
+        let teams = self.team_names().iter().map(|team_name| self.team(team_name).clone()).collect::<Vec<_>>();
+        batch::simulate_batch(&self.all_players, &teams, games, workers, seed)
+    }
+
+    /// Plays a batch of games like `simulate_batch` and returns the run expectancy matrix built from them, for weighing up strategies against the league as it stands rather than the season so far.
+    pub fn simulate_run_expectancy(&self, games: u32, workers: usize, seed: u64) -> RunExpectancy {
+        let teams = self.team_names().iter().map(|team_name| self.team(team_name).clone()).collect::<Vec<_>>();
+        batch::simulate_run_expectancy(&self.all_players, &teams, games, workers, seed)
+    }
+
+    /// Works out win probabilities, and the leverage that sets the granularity, from the run environment in the given matrix from now on, rather than major league play. Calibrating from `simulate_run_expectancy` matches them to how the simulation actually plays.
+    pub fn calibrate_win_probability(&mut self, run_expectancy: &RunExpectancy) {
+        self.win_probability = WinProbabilityModel::calibrated(run_expectancy);
+    }
+
+    /// Returns the run expectancy matrix built from every regular season game played this season.
+    pub fn run_expectancy(&self) -> &RunExpectancy {
+        &self.stats.run_expectancy
+    }
+
+    /// Returns what each play in the game that ended with the given `GameOver` output was worth, against this season's run expectancy and in the win probability that goes with it, or None for any other output.
+    pub fn evaluate_game(&self, game_over: &GameOutput) -> Option<Vec<PlayValue>> {
+        let GameOutput::GameOver { history, .. } = game_over else {
+            return None;
+        };
+        Some(analytics::evaluate_plays(history, &self.stats.run_expectancy))
+    }
+
+    /// Returns the RE24 and WPA each player piled up in the game that ended with the given `GameOver` output, or None for any other output.
+    pub fn game_player_values(&self, game_over: &GameOutput) -> Option<HashMap<PlayerId, PlayerValue>> {
+        self.evaluate_game(game_over).map(|plays| analytics::player_values(&plays))
+    }
+
+    /// Returns the RE24 and WPA each player has piled up this season, every play worked out against this season's run expectancy.
+    pub fn season_player_values(&self) -> HashMap<PlayerId, PlayerValue> {
+        self.stats.season_values()
+    }
+
+    /// Returns a text engine for a game between the given teams, knowing every player in the league and quoting this season's stats as they stand.
+    pub fn text_engine(&self, home_team: &str, visiting_team: &str) -> TextEngine {
+        TextEngine::new(self.known_players(), self.stats.season.clone(), home_team.to_string(), visiting_team.to_string())
+    }
+
+    /// Returns every player on a roster or in the free agent pool.
+    fn known_players(&self) -> HashMap<PlayerId, Player> {
+        self.all_players.iter().chain(self.free_agents.iter())
+            .map(|(id, player)| (*id, player.clone()))
+            .collect()
+    }
+
+    /// Returns a replay of the game that ended with the given `GameOver` output, or None for any other output. Its commentary doesn't quote season stats, since they've moved on since the game was played; swap in another text engine for that.
+    pub fn replay(&self, game_over: &GameOutput) -> Option<Replay> {
+        let GameOutput::GameOver { box_score, history, .. } = game_over else {
+            return None;
+        };
+        let text_engine = TextEngine::new(self.known_players(), HashMap::new(), box_score.home_team.clone(), box_score.visiting_team.clone());
+        Replay::new(history.clone(), text_engine)
+    }
+
+    /// Returns a replay of every game finished in the given event log, oldest first.
+    pub fn replay_log(&self, entries: &[LogEntry]) -> Vec<Replay> {
+        entries.iter().filter_map(|entry| self.replay(&entry.output)).collect()
+    }
+
+    /// Plays a new game from the given league and seed through the given inputs, as recorded from an earlier game, and returns a replay of every game finished along the way, oldest first. Inputs the earlier game turned down are turned down again, and change nothing.
+    pub fn replay_inputs(league: LeagueStructure, seed: u64, inputs: &[UserInput]) -> Vec<Replay> {
+        let mut game = Self::with_seed(league, seed);
+        let mut replays = Vec::new();
+        for input in inputs {
+            // players can retire once the season is over, so each game is set up for replay as soon as it ends, while everyone in it can still be named
+            if let Ok(game_output) = game.process_user_input(input) && let Some(replay) = game.replay(&game_output) {
+                replays.push(replay);
+            }
+        }
+        replays
+    }
+
+    /// Replaces the stats with ones saved by `save_stats` from this game. Stats kept for a game played from another seed are rejected.
+    pub fn load_stats(&mut self, path: &Path) -> io::Result<()> {
+        let stats = StatBook::load(path)?;
+        if stats.seed != Some(self.seed) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "the stats were kept for another game"));
+        }
+        self.stats = stats;
+        Ok(())
+    }
+
+    fn standings_output(&self) -> GameOutput {
+        let standings = self.season.division_standings(&self.league);
+        let description = text::describe_standings(&standings);
+
+        GameOutput::Standings { standings, description }
+    }
+
+    /// Returns the inputs that force a different granularity, or stop forcing one. Thresholds can be set at any time too, but there are too many to list.
+    fn granularity_inputs(&self) -> Vec<UserInput> {
+        let forced = self.granularity_settings.forced;
+        let mut user_inputs = Granularity::ALL.iter()
+            .filter(|granularity| forced != Some(**granularity))
+            .map(|granularity| UserInput::ForceGranularity(Some(*granularity)))
+            .collect::<Vec<_>>();
+        if forced.is_some() {
+            user_inputs.push(UserInput::ForceGranularity(None));
+        }
+        for thresholds in GranularityThresholds::presets() {
+            if thresholds != self.granularity_settings.thresholds {
+                user_inputs.push(UserInput::SetGranularityThresholds(thresholds));
+            }
+        }
+        user_inputs
+    }
+
+    fn granularity_output(&self) -> GameOutput {
+        GameOutput::GranularityOutput {
+            settings: self.granularity_settings.clone(),
+            description: text::describe_granularity_settings(&self.granularity_settings),
+        }
     }
 
     pub fn valid_user_inputs(&self) -> Vec<UserInput> {
         match &self.phase {
-            GamePhase::PreGame => vec![UserInput::StartNewGame],
-            GamePhase::InGame { baseball_game: current_game, .. } => {
+            GamePhase::PreGame => {
+                let mut user_inputs = vec![UserInput::StartNewGame, UserInput::ViewStandings, UserInput::ViewLeaders, UserInput::ViewLineup];
+                user_inputs.extend(self.lineup_inputs());
+                user_inputs.extend(self.granularity_inputs());
+                user_inputs
+            },
+            GamePhase::InGame { baseball_game: current_game, box_score, own_side, .. } => {
                 let game_state_summary = current_game.state_summary();
-                let granularity = Granularity::from_state_summary(&game_state_summary);
+                let granularity = self.granularity_settings.granularity(&game_state_summary, &self.win_probability);
 
-                match granularity {
+                let mut user_inputs = match granularity {
                     Granularity::Pitch => {
-                        let home_team_is_at_bat = current_game.home_team_is_at_bat();
-                        if home_team_is_at_bat {
+                        let own_team_is_at_bat = current_game.batting_side() == *own_side;
+                        if own_team_is_at_bat {
                             vec![UserInput::BatterDecision(BatterDecision::Swing), UserInput::BatterDecision(BatterDecision::Take)]
                         } else {
                             vec![UserInput::PitchAim(StrikeZoneLocation::In), UserInput::PitchAim(StrikeZoneLocation::Out)]
                         }
                     }
-                    Granularity::HalfInning => vec![UserInput::ContinuePlay],
+                    _ => vec![UserInput::ContinuePlay],
+                };
+                for power in usable_demon_powers(self.favors, &self.power_cooldowns, current_game, *own_side, granularity) {
+                    user_inputs.push(UserInput::UseDemonPower(power));
+                }
+                // the stadium is only rorzagoth's domain when the expos are at home
+                if *own_side == TeamSide::Home && current_game.batting_side() == *own_side {
+                    user_inputs.push(UserInput::SacrificeOut);
+                }
+                if current_game.batting_side() != *own_side {
+                    for reliever in available_relievers(current_game, box_score, *own_side) {
+                        user_inputs.push(UserInput::ChangePitcher(reliever));
+                    }
                 }
+                user_inputs.extend(self.granularity_inputs());
+
+                user_inputs
+            },
+            GamePhase::BetweenGames { trade_draft: Some(trade_draft), .. } => {
+                let own_roster = self.own_team.as_ref().unwrap().roster();
+                let other_roster = self.team(&trade_draft.team).roster();
+                let in_draft = |player: &PlayerId| trade_draft.offered.contains(player) || trade_draft.requested.contains(player);
+
+                let mut user_inputs = Vec::new();
+                for player in own_roster.iter().filter(|player| !in_draft(player)) {
+                    user_inputs.push(UserInput::OfferPlayer(*player));
+                }
+                for player in other_roster.iter().filter(|player| !in_draft(player)) {
+                    user_inputs.push(UserInput::RequestPlayer(*player));
+                }
+                for player in trade_draft.offered.iter().chain(trade_draft.requested.iter()) {
+                    user_inputs.push(UserInput::WithdrawPlayer(*player));
+                }
+                if trade_draft.pairings(&self.all_players).is_some() {
+                    user_inputs.push(UserInput::SubmitTrade);
+                }
+                user_inputs.push(UserInput::CancelTrade);
+                user_inputs.extend(self.granularity_inputs());
+
+                user_inputs
+            },
+            GamePhase::BetweenGames { has_prayed, trade_draft: None } => {
+                let mut user_inputs = Vec::new();
+                if !has_prayed {
+                    user_inputs.push(UserInput::PerformRitual(Ritual::Prayer));
+                }
+                if !self.free_agents.is_empty() {
+                    for player in self.own_team.as_ref().unwrap().active_players() {
+                        user_inputs.push(UserInput::PerformRitual(Ritual::BloodSacrifice(player)));
+                    }
+                }
+                let own_team = self.own_team.as_ref().unwrap();
+                if own_team.roster().len() < transactions::MAX_ROSTER_SIZE {
+                    for player in self.free_agents.keys() {
+                        user_inputs.push(UserInput::SignFreeAgent(*player));
+                    }
+                }
+                for player in own_team.bench.iter().chain(own_team.bullpen.iter()) {
+                    user_inputs.push(UserInput::ReleasePlayer(*player));
+                }
+                for team_name in self.other_teams.keys() {
+                    user_inputs.push(UserInput::StartTradeTalks(team_name.clone()));
+                }
+                if self.season_is_finished() {
+                    user_inputs.push(UserInput::StartNextSeason);
+                } else {
+                    user_inputs.push(UserInput::AdvanceToNextGame);
+                }
+                user_inputs.push(UserInput::ViewStandings);
+                user_inputs.push(UserInput::ViewLeaders);
+                user_inputs.push(UserInput::ViewTransactions);
+                user_inputs.extend(self.granularity_inputs());
+
+                user_inputs
             },
-            GamePhase::BetweenGames => todo!(),
         }
     }
 
+    /// Carries out the user's input and returns what happened, writing both to the event log if there is one.
     pub fn process_user_input(&mut self, user_input: &UserInput) -> Result<GameOutput, GameError> {
-        match &mut self.phase {
-            GamePhase::PreGame => {
-                let UserInput::StartNewGame = user_input else {
+        let game_output = self.apply_user_input(user_input)?;
+        if let Some(event_log) = self.event_log.as_mut() {
+            event_log.append(user_input, &game_output).map_err(GameError::EventLog)?;
+        }
+
+        Ok(game_output)
+    }
+
+    fn apply_user_input(&mut self, user_input: &UserInput) -> Result<GameOutput, GameError> {
+        match user_input {
+            UserInput::SetGranularityThresholds(thresholds) => {
+                if !thresholds.are_in_order() {
                     return Err(GameError::InvalidUserInput);
-                };
+                }
+                self.granularity_settings.thresholds = thresholds.clone();
+                return Ok(self.granularity_output());
+            },
+            UserInput::ForceGranularity(forced) => {
+                self.granularity_settings.forced = *forced;
+                return Ok(self.granularity_output());
+            },
+            _ => {},
+        }
 
-                self.start_new_game();
+        match &mut self.phase {
+            GamePhase::PreGame => match user_input {
+                UserInput::StartNewGame => {
+                    self.start_new_game()?;
+
+                    Ok(GameOutput::StartNewGame)
+                },
+                UserInput::ViewStandings => Ok(self.standings_output()),
+                UserInput::ViewLeaders => Ok(self.leaders_output()),
+                UserInput::ViewLineup => self.lineup_output(),
+                UserInput::SwapBattingOrder(a, b) => {
+                    let own_team = self.own_team.as_mut().unwrap();
+                    if *a >= own_team.batting_order.len() || *b >= own_team.batting_order.len() {
+                        return Err(GameError::InvalidUserInput);
+                    }
+                    own_team.swap_batting_order(*a, *b);
+
+                    self.lineup_output()
+                },
+                UserInput::AssignFielder(position, player) => {
+                    let own_team = self.own_team.as_mut().unwrap();
+                    let is_position_player = own_team.batting_order.contains(player) || own_team.bench.contains(player);
+                    if *position == Position::Pitcher || !is_position_player {
+                        return Err(GameError::InvalidUserInput);
+                    }
+                    own_team.assign_fielder(*position, *player);
+
+                    self.lineup_output()
+                },
+                UserInput::SetDesignatedHitter(player) => {
+                    let own_team = self.own_team.as_mut().unwrap();
+                    if !own_team.bench.contains(player) || own_team.designated_hitter().is_none() {
+                        return Err(GameError::InvalidUserInput);
+                    }
+                    own_team.set_designated_hitter(*player);
 
-                Ok(GameOutput::StartNewGame)
+                    self.lineup_output()
+                },
+                UserInput::ChooseStartingPitcher(pitcher) => {
+                    let own_team = self.own_team.as_mut().unwrap();
+                    if !own_team.all_pitchers.contains(pitcher) {
+                        return Err(GameError::InvalidUserInput);
+                    }
+                    own_team.current_pitcher = *pitcher;
+
+                    self.lineup_output()
+                },
+                UserInput::SkipStarter => {
+                    self.own_team.as_mut().unwrap().skip_starter();
+
+                    self.lineup_output()
+                },
+                UserInput::SwapRotation(a, b) => {
+                    let own_team = self.own_team.as_mut().unwrap();
+                    if *a >= own_team.all_pitchers.len() || *b >= own_team.all_pitchers.len() {
+                        return Err(GameError::InvalidUserInput);
+                    }
+                    own_team.swap_rotation(*a, *b);
+
+                    self.lineup_output()
+                },
+                _ => Err(GameError::InvalidUserInput),
             },
-            GamePhase::InGame { baseball_game: current_game, text_engine } => {
+            GamePhase::InGame { baseball_game: current_game, text_engine, box_score, own_side, fixture } => {
                 let game_state_summary = current_game.state_summary();
-                let granularity = Granularity::from_state_summary(&game_state_summary);
-                let home_team_is_at_bat = current_game.home_team_is_at_bat();
+                let granularity = self.granularity_settings.granularity(&game_state_summary, &self.win_probability);
+
+                if let UserInput::UseDemonPower(power) = user_input {
+                    let usable_powers = usable_demon_powers(self.favors, &self.power_cooldowns, current_game, *own_side, granularity);
+                    if !usable_powers.contains(power) {
+                        return Err(GameError::InvalidUserInput);
+                    }
+
+                    let spec = demon::spec(*power);
+                    self.favors -= spec.favor_cost;
+                    self.power_cooldowns.insert(*power, spec.cooldown);
 
-                let game_output = match (granularity, user_input, home_team_is_at_bat) {
+                    let backfired = !demon::power_succeeds(spec, self.goodwill, &mut self.rng);
+                    let request = demon::PowerRequest { own_side: *own_side, goodwill: self.goodwill, backfired };
+                    let effect = (spec.effect)(current_game, request);
+                    let description = text_engine.describe_demon_power(&effect, backfired);
+
+                    return Ok(GameOutput::DemonPowerOutput {
+                        power: *power,
+                        backfired,
+                        favors: self.favors,
+                        description,
+                    });
+                }
+
+                if let UserInput::SacrificeOut = user_input {
+                    if *own_side != TeamSide::Home || current_game.batting_side() != *own_side {
+                        return Err(GameError::InvalidUserInput);
+                    }
+
+                    current_game.sacrifice_out();
+                    self.favors += demon::FAVORS_PER_SACRIFICED_OUT;
+                    let new_game_state_summary = current_game.state_summary();
+                    box_score.record_sacrificed_out(&game_state_summary, &new_game_state_summary);
+                    let description = text_engine.describe_sacrificed_out(&game_state_summary, &new_game_state_summary);
+                    let game_output = GameOutput::SacrificeOutOutput {
+                        new_game_state_summary,
+                        favors: self.favors,
+                        description,
+                    };
+                    if current_game.state.game_outcome != GameOutcome::Ongoing {
+                        let (fixture, result, box_score, history) = (*fixture, current_game.result(), *box_score.clone(), std::mem::take(&mut current_game.history));
+                        return Ok(self.game_over_output(fixture, result, box_score, history, game_output));
+                    }
+
+                    return Ok(game_output);
+                }
+
+                if let UserInput::ChangePitcher(reliever) = user_input {
+                    if current_game.batting_side() == *own_side || !available_relievers(current_game, box_score, *own_side).contains(reliever) {
+                        return Err(GameError::InvalidUserInput);
+                    }
+
+                    current_game.change_pitcher(*own_side, *reliever);
+                    return Ok(GameOutput::PitchingChangeOutput {
+                        pitcher: *reliever,
+                        description: text_engine.describe_pitching_change(*own_side, *reliever),
+                    });
+                }
+
+                let own_team_is_at_bat = current_game.batting_side() == *own_side;
+                let mut pitching_changes = Vec::new(); // descriptions of the opponents' changes, which go before the play
+
+                let mut game_output = match (granularity, user_input, own_team_is_at_bat) {
                     (Granularity::Pitch, UserInput::BatterDecision(decision), true) => {
+                        pitching_changes.extend(change_opponent_pitcher(current_game, box_score, text_engine, *own_side));
+                        let game_state_summary = current_game.state_summary();
                         let events_summary = current_game.simulate_pitch(None, Some(*decision));
-                        let new_game_state_summary = current_game.state_summary();
-                        let description = text_engine.describe_pitch_level_summaries(
-                            &game_state_summary,
-                            &events_summary,
-                            &new_game_state_summary,
-                        );
-
-                        GameOutput::PitchOutput {
-                            events_summary,
-                            new_game_state_summary,
-                            description,
-                        }
+                        pitch_output(current_game, box_score, text_engine, &game_state_summary, events_summary, &self.win_probability)
                     },
                     (Granularity::Pitch, UserInput::PitchAim(location), false) => {
                         let events_summary = current_game.simulate_pitch(Some(*location), None);
-                        let new_game_state_summary = current_game.state_summary();
-                        let description = text_engine.describe_pitch_level_summaries(
-                            &game_state_summary,
-                            &events_summary,
-                            &new_game_state_summary,
-                        );
-
-                        GameOutput::PitchOutput {
-                            events_summary,
-                            new_game_state_summary,
-                            description,
-                        }
+                        pitch_output(current_game, box_score, text_engine, &game_state_summary, events_summary, &self.win_probability)
                     },
-                    (Granularity::HalfInning, UserInput::ContinuePlay, _) => {
-                        let current_half = game_state_summary.half_inning.top;
+                    (_, UserInput::ContinuePlay, _) if granularity != Granularity::Pitch => {
                         let mut events_summaries = Vec::new();
                         let mut game_state_summaries = Vec::new();
                         loop {
+                            pitching_changes.extend(change_opponent_pitcher(current_game, box_score, text_engine, *own_side));
+                            let prev_state = current_game.state_summary();
                             let events_summary = current_game.simulate_pitch(None, None);
+                            let new_state = current_game.state_summary();
+                            box_score.record(&prev_state, &new_state, &events_summary);
                             events_summaries.push(events_summary);
-                            let game_state_summary = current_game.state_summary();
-                            game_state_summaries.push(game_state_summary);
-                            if game_state_summaries.last().unwrap().half_inning.top != current_half {
+                            // play stops early for the next batter if things have got tense enough to go pitch by pitch
+                            let is_tense = new_state.count.is_empty() && self.granularity_settings.granularity(&new_state, &self.win_probability) == Granularity::Pitch;
+                            let is_over = granularity.is_over(&game_state_summary, &new_state) || is_tense;
+                            game_state_summaries.push(new_state);
+                            if is_over || current_game.state.game_outcome != GameOutcome::Ongoing {
                                 break;
                             }
                         }
-                        let description = text_engine.describe_half_inning_summaries(
+                        let description = text_engine.describe_play_summaries(
                             &game_state_summary,
                             &events_summaries,
                             &game_state_summaries,
                         );
 
-                        GameOutput::HalfInningOutput {
+                        GameOutput::PlayOutput {
+                            granularity,
                             events_summaries,
                             game_state_summaries,
                             description,
@@ -281,10 +1593,327 @@ impl Game {
                     },
                     _ => return Err(GameError::InvalidUserInput),
                 };
+                if let GameOutput::PitchOutput { description, .. } | GameOutput::PlayOutput { description, .. } = &mut game_output {
+                    pitching_changes.push(std::mem::take(description));
+                    *description = pitching_changes.join(" ");
+                }
+
+                let pitches = match &game_output {
+                    GameOutput::PlayOutput { events_summaries, .. } => events_summaries.len() as u32,
+                    _ => 1,
+                };
+                for cooldown in self.power_cooldowns.values_mut() {
+                    *cooldown = cooldown.saturating_sub(pitches);
+                }
+
+                if current_game.state.game_outcome != GameOutcome::Ongoing {
+                    let (fixture, result, box_score, history) = (*fixture, current_game.result(), *box_score.clone(), std::mem::take(&mut current_game.history));
+                    return Ok(self.game_over_output(fixture, result, box_score, history, game_output));
+                }
+
+                Ok(game_output)
+            },
+            GamePhase::BetweenGames { trade_draft: Some(trade_draft), .. } => {
+                let mut trade_draft = trade_draft.clone();
+                let own_roster = self.own_team.as_ref().unwrap().roster();
+                let other_roster = self.team(&trade_draft.team).roster();
+                let in_draft = |player: &PlayerId| trade_draft.offered.contains(player) || trade_draft.requested.contains(player);
+
+                let game_output = match user_input {
+                    UserInput::OfferPlayer(player) if own_roster.contains(player) && !in_draft(player) => {
+                        trade_draft.offered.push(*player);
+                        self.trade_talks_output(&trade_draft)
+                    },
+                    UserInput::RequestPlayer(player) if other_roster.contains(player) && !in_draft(player) => {
+                        trade_draft.requested.push(*player);
+                        self.trade_talks_output(&trade_draft)
+                    },
+                    UserInput::WithdrawPlayer(player) if in_draft(player) => {
+                        trade_draft.offered.retain(|offered| offered != player);
+                        trade_draft.requested.retain(|requested| requested != player);
+                        self.trade_talks_output(&trade_draft)
+                    },
+                    UserInput::SubmitTrade => {
+                        let accepted = self.submit_trade(&trade_draft)?;
+                        let description = text::describe_trade_response(&trade_draft.team, accepted);
+                        if accepted {
+                            self.set_trade_draft(None);
+                            return Ok(GameOutput::TradeResponse { accepted, description });
+                        }
+                        GameOutput::TradeResponse { accepted, description }
+                    },
+                    UserInput::CancelTrade => {
+                        self.set_trade_draft(None);
+                        return Ok(GameOutput::TradeResponse {
+                            accepted: false,
+                            description: text::describe_trade_response(&trade_draft.team, false),
+                        });
+                    },
+                    _ => return Err(GameError::InvalidUserInput),
+                };
+                self.set_trade_draft(Some(trade_draft));
+
+                Ok(game_output)
+            },
+            GamePhase::BetweenGames { has_prayed, trade_draft: None } => {
+                let game_output = match user_input {
+                    UserInput::PerformRitual(Ritual::Prayer) if !*has_prayed => {
+                        *has_prayed = true;
+                        self.goodwill = (self.goodwill + demon::PRAYER_GOODWILL).min(1.0);
+
+                        GameOutput::RitualOutput {
+                            ritual: Ritual::Prayer,
+                            goodwill: self.goodwill,
+                            description: text::describe_prayer(),
+                        }
+                    },
+                    UserInput::PerformRitual(Ritual::BloodSacrifice(victim)) => {
+                        let is_own_player = self.own_team.as_ref().unwrap().active_players().contains(victim);
+                        if !is_own_player || self.free_agents.is_empty() {
+                            return Err(GameError::InvalidUserInput);
+                        }
+
+                        let victim_name = self.player_name(*victim).to_string();
+                        let replacement = self.sacrifice_player(*victim);
+                        self.goodwill = (self.goodwill + demon::BLOOD_SACRIFICE_GOODWILL).min(1.0);
+
+                        GameOutput::RitualOutput {
+                            ritual: Ritual::BloodSacrifice(*victim),
+                            goodwill: self.goodwill,
+                            description: text::describe_blood_sacrifice(&victim_name, self.player_name(replacement)),
+                        }
+                    },
+                    UserInput::SignFreeAgent(player) => {
+                        if !self.free_agents.contains_key(player) || self.own_team.as_ref().unwrap().roster().len() >= transactions::MAX_ROSTER_SIZE {
+                            return Err(GameError::InvalidUserInput);
+                        }
+                        self.sign_free_agent(*player)
+                    },
+                    UserInput::ReleasePlayer(player) => {
+                        let own_team = self.own_team.as_ref().unwrap();
+                        if !own_team.bench.contains(player) && !own_team.bullpen.contains(player) {
+                            return Err(GameError::InvalidUserInput);
+                        }
+                        self.release_player(*player)
+                    },
+                    UserInput::StartTradeTalks(team_name) => {
+                        if !self.other_teams.contains_key(team_name) {
+                            return Err(GameError::InvalidUserInput);
+                        }
+                        let trade_draft = TradeDraft::new(team_name.clone());
+                        let game_output = self.trade_talks_output(&trade_draft);
+                        self.set_trade_draft(Some(trade_draft));
+                        game_output
+                    },
+                    UserInput::ViewTransactions => self.transactions_output(),
+                    UserInput::AdvanceToNextGame => self.advance_to_next_game()?,
+                    UserInput::StartNextSeason if self.season_is_finished() => self.start_next_season(),
+                    UserInput::ViewStandings => self.standings_output(),
+                    UserInput::ViewLeaders => self.leaders_output(),
+                    _ => return Err(GameError::InvalidUserInput),
+                };
 
                 Ok(game_output)
             },
-            GamePhase::BetweenGames => todo!(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays the expos' first game through with the simplest input on offer each time, returning the inputs given and the last output.
+    fn play_first_game(game: &mut Game) -> (Vec<UserInput>, GameOutput) {
+        let mut inputs = vec![UserInput::StartNewGame];
+        let mut game_output = game.process_user_input(&UserInput::StartNewGame).unwrap();
+        while let GamePhase::InGame { .. } = game.phase {
+            let valid = game.valid_user_inputs();
+            let input = if valid.iter().any(|input| matches!(input, UserInput::ContinuePlay)) {
+                UserInput::ContinuePlay
+            } else if valid.iter().any(|input| matches!(input, UserInput::PitchAim(_))) {
+                UserInput::PitchAim(StrikeZoneLocation::In)
+            } else {
+                UserInput::BatterDecision(BatterDecision::Swing)
+            };
+            game_output = game.process_user_input(&input).unwrap();
+            inputs.push(input);
+        }
+        (inputs, game_output)
+    }
+
+    #[test]
+    fn replaying_inputs_from_the_seed_plays_the_same_game() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        let (inputs, _) = play_first_game(&mut game);
+        let replays = Game::replay_inputs(LeagueStructure::default(), 7, &inputs);
+        assert_eq!(replays.len(), 1);
+
+        let mut replay = Game::replay_inputs(LeagueStructure::default(), 7, &inputs).pop().unwrap();
+        assert_eq!(format!("{:?}", replay.states()), format!("{:?}", replays[0].states()));
+        assert_eq!((replay.state().half_inning.number, replay.state().outs), (1, 0));
+
+        let first_play = replay.step_forward().unwrap();
+        let after_first_play = format!("{:?}", replay.state());
+        assert_eq!(replay.step_back(), Some(first_play));
+        assert!(replay.step_back().is_none());
+        replay.seek(1);
+        assert_eq!(format!("{:?}", replay.state()), after_first_play);
+
+        replay.seek(usize::MAX);
+        assert!(replay.step_forward().is_none());
+        assert_eq!(replay.position(), replay.len());
+        assert_ne!(replay.state().home_team_runs, replay.state().visiting_team_runs);
+    }
+
+    #[test]
+    fn games_can_be_replayed_after_their_players_are_gone() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        let (_, game_over) = play_first_game(&mut game);
+        let leadoff = game.own_team.as_ref().unwrap().batting_order[0];
+        game.process_user_input(&UserInput::PerformRitual(Ritual::BloodSacrifice(leadoff))).unwrap();
+        assert!(!game.all_players.contains_key(&leadoff));
+
+        let mut replay = game.replay(&game_over).unwrap();
+        let mut commentary = Vec::new();
+        while let Some(play) = replay.step_forward() {
+            commentary.push(play);
+        }
+        assert!(commentary.iter().any(|play| play.contains("(gone)")));
+    }
+
+    #[test]
+    fn lineup_inputs_are_checked_when_given() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        assert!(matches!(game.process_user_input(&UserInput::SwapBattingOrder(0, 9)), Err(GameError::InvalidUserInput)));
+        assert!(matches!(game.process_user_input(&UserInput::AssignFielder(Position::Pitcher, PlayerId(0))), Err(GameError::InvalidUserInput)));
+
+        let own_team = game.own_team.as_ref().unwrap();
+        let (leadoff, second) = (own_team.batting_order[0], own_team.batting_order[1]);
+        let Ok(GameOutput::Lineup { batting_order, .. }) = game.process_user_input(&UserInput::SwapBattingOrder(0, 1)) else {
+            panic!("swapping the first two hitters should show the lineup");
+        };
+        assert_eq!((batting_order[0], batting_order[1]), (second, leadoff));
+
+        let own_team = game.own_team.clone().unwrap();
+        let valid = game.valid_user_inputs();
+        let bench_player = own_team.bench[0];
+        let other_starter = own_team.all_pitchers.iter().copied().find(|pitcher| *pitcher != own_team.current_pitcher).unwrap();
+        assert!(valid.iter().any(|input| matches!(input, UserInput::SwapBattingOrder(0, 8))));
+        assert!(valid.iter().any(|input| matches!(input, UserInput::AssignFielder(Position::Catcher, player) if *player == bench_player)));
+        assert!(valid.iter().any(|input| matches!(input, UserInput::ChooseStartingPitcher(pitcher) if *pitcher == other_starter)));
+        assert!(!valid.iter().any(|input| matches!(input, UserInput::ChooseStartingPitcher(pitcher) if *pitcher == own_team.current_pitcher)));
+        assert!(!valid.iter().any(|input| matches!(input, UserInput::AssignFielder(Position::Catcher, player) if *player == own_team.fielders[0])));
+        assert!(!valid.iter().any(|input| matches!(input, UserInput::AssignFielder(_, player) if own_team.all_pitchers.contains(player))));
+        if own_team.designated_hitter().is_some() {
+            assert!(valid.iter().any(|input| matches!(input, UserInput::SetDesignatedHitter(player) if *player == bench_player)));
+        }
+
+        // every change still on offer after the ones before it goes through and leaves a lineup that can take the field
+        let lineup_changes = valid.into_iter().filter(|input| !matches!(
+            input,
+            UserInput::StartNewGame | UserInput::ViewStandings | UserInput::ViewLeaders | UserInput::ViewLineup
+                | UserInput::SetGranularityThresholds(_) | UserInput::ForceGranularity(_)
+        ));
+        for input in lineup_changes {
+            if !game.valid_user_inputs().iter().any(|valid_input| format!("{valid_input:?}") == format!("{input:?}")) {
+                continue;
+            }
+            assert!(game.process_user_input(&input).is_ok(), "{input:?} was offered but refused");
+            assert_eq!(game.own_team.as_ref().unwrap().validate_lineup(&game.all_players), Ok(()), "{input:?} broke the lineup");
+        }
+    }
+
+    #[test]
+    fn games_fit_their_season_to_a_small_league() {
+        let league = LeagueStructure::parse("A,East,Montreal,Expos,MON\nA,East,Boston,Red Sox,BOS\nA,West,San Diego,Padres,SD").unwrap();
+        let mut game = Game::with_seed(league, 7);
+        assert_eq!(game.season.config.games_per_team, 10);
+        assert_eq!(game.postseason_config.num_teams(), 2);
+
+        play_first_game(&mut game);
+        let standings = game.standings();
+        assert_eq!(standings.len(), 3);
+        assert_eq!(standings.iter().map(|row| row.wins + row.losses).sum::<u32>(), 2);
+    }
+
+    #[test]
+    fn granularity_threshold_presets_are_offered() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        let preset = |game: &Game| game.valid_user_inputs().into_iter().find_map(|input| match input {
+            UserInput::SetGranularityThresholds(thresholds) => Some(thresholds),
+            _ => None,
+        });
+        let closer = preset(&game).unwrap();
+        assert_eq!(closer, GranularityThresholds::default().scaled(0.5));
+
+        game.process_user_input(&UserInput::SetGranularityThresholds(closer.clone())).unwrap();
+        assert_eq!(game.granularity_settings.thresholds, closer);
+        assert_eq!(preset(&game), Some(GranularityThresholds::default()));
+    }
+
+    #[test]
+    fn expos_cannot_sign_past_a_full_roster() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        play_first_game(&mut game);
+        let own_team = game.own_team.as_ref().unwrap();
+        let spare = own_team.bench.first().or(own_team.bullpen.first()).copied().unwrap();
+        assert_eq!(own_team.roster().len(), transactions::MAX_ROSTER_SIZE);
+
+        let free_agent = *game.free_agents.keys().min().unwrap();
+        assert!(!game.valid_user_inputs().iter().any(|input| matches!(input, UserInput::SignFreeAgent(_))));
+        assert!(matches!(game.process_user_input(&UserInput::SignFreeAgent(free_agent)), Err(GameError::InvalidUserInput)));
+
+        game.process_user_input(&UserInput::ReleasePlayer(spare)).unwrap();
+        assert!(game.free_agents.contains_key(&spare));
+        game.process_user_input(&UserInput::SignFreeAgent(free_agent)).unwrap();
+        assert!(game.own_team.as_ref().unwrap().roster().contains(&free_agent));
+        assert_eq!(game.own_team.as_ref().unwrap().roster().len(), transactions::MAX_ROSTER_SIZE);
+    }
+
+    #[test]
+    fn stats_only_load_into_the_game_they_were_kept_for() {
+        let mut game = Game::with_seed(LeagueStructure::default(), 7);
+        play_first_game(&mut game);
+        let path = std::env::temp_dir().join(format!("idbb-game-stats-{}.json", std::process::id()));
+        game.save_stats(&path).unwrap();
+
+        let mut other_game = Game::with_seed(LeagueStructure::default(), 8);
+        assert_eq!(other_game.load_stats(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut same_game = Game::with_seed(LeagueStructure::default(), 7);
+        same_game.load_stats(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(same_game.stats.season.len(), game.stats.season.len());
+    }
+
+    #[test]
+    fn generated_rosters_share_no_players() {
+        let game = Game::with_seed(LeagueStructure::default(), 2);
+        let mut rostered = HashSet::new();
+        for team_name in game.team_names() {
+            let roster = game.team(&team_name).roster();
+            assert_eq!(roster.len(), 26);
+            for player in roster {
+                assert!(rostered.insert(player), "{player:?} is on more than one roster");
+                assert!(game.all_players.contains_key(&player) && !game.free_agents.contains_key(&player));
+            }
+        }
+        assert_eq!(rostered.len(), game.all_players.len());
+
+        let names = game.all_players.values().chain(game.free_agents.values()).map(|player| &player.name).collect::<HashSet<_>>();
+        assert_eq!(names.len(), game.all_players.len() + game.free_agents.len());
+    }
+
+    #[test]
+    fn names_stay_unique_however_crowded_the_league_gets() {
+        let mut player_generator = PlayerGenerator::new();
+        let mut rng = GameRng::seed_from_u64(0);
+        player_generator.generate_free_agents(5000, &mut rng);
+        let names = player_generator.players.values().map(|player| player.name.clone()).collect::<HashSet<_>>();
+        assert_eq!(names.len(), 5000);
+
+        let name = names.iter().next().unwrap();
+        player_generator.free_name(name);
+        assert!(!player_generator.names_in_use.contains(name));
+    }
+}
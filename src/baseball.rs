@@ -1,7 +1,13 @@
+use crate::demon;
+use crate::season::GameResult;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
+pub const FULL_REST_DAYS: u32 = 4; // days off a starter needs between starts to pitch at full strength
+const SHORT_REST_PENALTY_PER_DAY: f64 = 0.15; // fraction of a starter's pitching lost for each day of rest short
+
 #[derive(Copy, Clone, Debug)]
 pub enum Level {
     VeryHigh,
@@ -11,9 +17,9 @@ pub enum Level {
     VeryLow,
 }
 
-impl Into<f64> for Level {
-    fn into(self) -> f64 {
-        match self {
+impl From<Level> for f64 {
+    fn from(level: Level) -> f64 {
+        match level {
             Level::VeryHigh => 1.0,
             Level::High => 0.75,
             Level::Medium => 0.5,
@@ -23,6 +29,64 @@ impl Into<f64> for Level {
     }
 }
 
+impl Level {
+    /// Returns the level one step up or down, or None if that would reach one of the extremes, which players never develop into.
+    fn step(self, up: bool) -> Option<Level> {
+        match (self, up) {
+            (Level::Low, true) => Some(Level::Medium),
+            (Level::Medium, true) => Some(Level::High),
+            (Level::High, false) => Some(Level::Medium),
+            (Level::Medium, false) => Some(Level::Low),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Position {
+    Pitcher,
+    Catcher,
+    FirstBase,
+    SecondBase,
+    ThirdBase,
+    Shortstop,
+    LeftField,
+    CenterField,
+    RightField,
+}
+
+impl Position {
+    /// The positions played behind the pitcher, in the order `Team::fielders` lists them.
+    pub const FIELDING: [Position; 8] = [
+        Position::Catcher,
+        Position::FirstBase,
+        Position::SecondBase,
+        Position::ThirdBase,
+        Position::Shortstop,
+        Position::LeftField,
+        Position::CenterField,
+        Position::RightField,
+    ];
+
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Position::Pitcher => "P",
+            Position::Catcher => "C",
+            Position::FirstBase => "1B",
+            Position::SecondBase => "2B",
+            Position::ThirdBase => "3B",
+            Position::Shortstop => "SS",
+            Position::LeftField => "LF",
+            Position::CenterField => "CF",
+            Position::RightField => "RF",
+        }
+    }
+}
+
+/// Identifies a player for their whole career, whatever their name.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub struct PlayerId(pub u32);
+
 #[derive(Debug, Clone)]
 pub struct PlayerMetrics {
     hitting: Level,
@@ -32,7 +96,7 @@ pub struct PlayerMetrics {
 }
 
 impl PlayerMetrics {
-    pub fn random(rng: &mut ThreadRng) -> Self {
+    pub fn random(rng: &mut GameRng) -> Self {
         let allowed_levels = [Level::High, Level::Medium, Level::Low];
         let hitting = *allowed_levels.choose(rng).unwrap();
         let running = *allowed_levels.choose(rng).unwrap();
@@ -41,25 +105,315 @@ impl PlayerMetrics {
 
         Self { hitting, running, fielding, pitching }
     }
+
+    /// Returns random metrics suited to the given position, avoiding the extremes like `random` does. Pitchers pitch and rarely hit, catchers and middle infielders field, and corner players hit.
+    pub fn for_position(position: Position, rng: &mut GameRng) -> Self {
+        let strong: &[Level] = &[Level::High, Level::High, Level::Medium];
+        let average: &[Level] = &[Level::High, Level::Medium, Level::Low];
+        let weak: &[Level] = &[Level::Medium, Level::Low, Level::Low];
+
+        let (hitting, running, fielding, pitching) = match position {
+            Position::Pitcher => (weak, weak, average, strong),
+            Position::Catcher => (average, weak, strong, weak),
+            Position::FirstBase => (strong, weak, average, weak),
+            Position::SecondBase | Position::Shortstop => (average, strong, strong, weak),
+            Position::ThirdBase => (strong, average, average, weak),
+            Position::LeftField | Position::RightField => (strong, average, average, weak),
+            Position::CenterField => (average, strong, strong, weak),
+        };
+
+        Self {
+            hitting: *hitting.choose(rng).unwrap(),
+            running: *running.choose(rng).unwrap(),
+            fielding: *fielding.choose(rng).unwrap(),
+            pitching: *pitching.choose(rng).unwrap(),
+        }
+    }
+
+    /// Returns how good a player with these metrics is at the given position, between 0 and 1.
+    pub fn overall(&self, position: Position) -> f64 {
+        let [hitting, running, fielding, pitching]: [f64; 4] = [self.hitting, self.running, self.fielding, self.pitching].map(f64::from);
+        match position {
+            Position::Pitcher => 0.8 * pitching + 0.2 * fielding,
+            _ => 0.5 * hitting + 0.3 * fielding + 0.2 * running,
+        }
+    }
+
+    /// Moves a random one of the metrics that count at the given position a level up or down, staying clear of the extremes. Returns false if none of them can move that way.
+    pub fn nudge(&mut self, position: Position, up: bool, rng: &mut GameRng) -> bool {
+        let metrics = match position {
+            Position::Pitcher => vec![&mut self.pitching, &mut self.fielding],
+            _ => vec![&mut self.hitting, &mut self.fielding, &mut self.running],
+        };
+        let Some(metric) = metrics.into_iter().filter(|metric| metric.step(up).is_some()).choose(rng) else {
+            return false;
+        };
+        *metric = metric.step(up).unwrap();
+
+        true
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Contract {
+    pub salary: u32, // in thousands of dollars a year
+    pub years: u32, // left to run, including the current season
 }
 
 #[derive(Debug, Clone)]
 pub struct Player {
+    pub id: PlayerId,
     pub name: String,
+    pub position: Position,
+    pub age: u32,
+    pub potential: f64, // the best overall rating the player can develop into, on the same scale as `PlayerMetrics::overall`
+    pub contract: Option<Contract>, // None while unsigned
     pub metrics: PlayerMetrics,
 }
 
 #[derive(Debug, Clone)]
 pub struct Team {
     pub name: String,
-    pub batting_order: [String; 9],
-    pub all_pitchers: Vec<String>,
-    pub current_pitcher: String,
-    pub fielders: [String; 8], // does not include the pitcher
-    pub bullpen: Vec<String>,
+    pub batting_order: [PlayerId; 9],
+    pub all_pitchers: Vec<PlayerId>, // the starting rotation, in order
+    pub rotation_index: usize, // the place in the rotation whose turn it is to start
+    pub days_of_rest: HashMap<PlayerId, u32>, // days since each pitcher last started; pitchers who haven't are fully rested
+    pub current_pitcher: PlayerId,
+    pub fielders: [PlayerId; 8], // does not include the pitcher, ordered as in `Position::FIELDING`
+    pub bench: Vec<PlayerId>,
+    pub bullpen: Vec<PlayerId>,
+}
+
+/// Why a lineup can't take the field.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LineupError {
+    DuplicatePlayer(PlayerId), // holds more than one place in the batting order, in the field, or both batting and pitching
+    UnavailablePlayer(PlayerId), // no longer in the league, e.g. retired or sacrificed
+    FielderNotBatting(PlayerId),
+    StarterNotInRotation(PlayerId),
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum TeamSide {
+    Home,
+    Visiting,
+}
+
+impl TeamSide {
+    pub fn other(&self) -> Self {
+        match self {
+            Self::Home => Self::Visiting,
+            Self::Visiting => Self::Home,
+        }
+    }
+}
+
+/// Invisible walls placed around every player on one side. While they stand, that side's fielders cannot move and its runners cannot take extra bases.
 #[derive(Debug, Clone)]
+pub struct Mime {
+    pub side: TeamSide,
+    pub plays_remaining: u8,
+}
+
+impl Team {
+    /// Returns every player holding a place in the batting order, in the field or in the rotation, each once.
+    pub fn active_players(&self) -> Vec<PlayerId> {
+        let mut players = Vec::new();
+        for player in self.batting_order.iter().chain(self.fielders.iter()).chain(self.all_pitchers.iter()) {
+            if !players.contains(player) {
+                players.push(*player);
+            }
+        }
+        players
+    }
+
+    /// Returns everyone on the roster: the active players, the bench and the bullpen.
+    pub fn roster(&self) -> Vec<PlayerId> {
+        let mut players = self.active_players();
+        players.extend(self.bench.iter());
+        players.extend(self.bullpen.iter());
+        players
+    }
+
+    /// Returns the player in the batting order who doesn't take the field, if there is exactly one.
+    pub fn designated_hitter(&self) -> Option<PlayerId> {
+        let mut hitters = self.batting_order.iter().filter(|player| !self.fielders.contains(player));
+        match (hitters.next(), hitters.next()) {
+            (Some(player), None) => Some(*player),
+            _ => None,
+        }
+    }
+
+    pub fn swap_batting_order(&mut self, a: usize, b: usize) {
+        self.batting_order.swap(a, b);
+    }
+
+    /// Puts `player` at `position`. If they were already in the field or the designated hitter, they trade places with whoever played there. If they came off the bench, they take that player's place in the batting order and that player goes to the bench.
+    pub fn assign_fielder(&mut self, position: Position, player: PlayerId) {
+        let index = Position::FIELDING.iter().position(|fielding_position| *fielding_position == position).unwrap();
+        let displaced = self.fielders[index];
+        if displaced == player {
+            return;
+        }
+
+        if let Some(other_index) = self.fielders.iter().position(|fielder| *fielder == player) {
+            self.fielders.swap(index, other_index);
+        } else if Some(player) == self.designated_hitter() {
+            self.fielders[index] = player;
+        } else if let Some(bench_index) = self.bench.iter().position(|bench_player| *bench_player == player) {
+            self.fielders[index] = player;
+            self.bench[bench_index] = displaced;
+            for slot in self.batting_order.iter_mut() {
+                if *slot == displaced {
+                    *slot = player;
+                }
+            }
+        }
+    }
+
+    /// Makes `player`, who must be on the bench, the designated hitter, sending the current one to the bench.
+    pub fn set_designated_hitter(&mut self, player: PlayerId) {
+        let Some(designated_hitter) = self.designated_hitter() else {
+            return;
+        };
+        if let Some(bench_index) = self.bench.iter().position(|bench_player| *bench_player == player) {
+            self.bench[bench_index] = designated_hitter;
+            for slot in self.batting_order.iter_mut() {
+                if *slot == designated_hitter {
+                    *slot = player;
+                }
+            }
+        }
+    }
+
+    pub fn days_of_rest(&self, pitcher: PlayerId) -> u32 {
+        self.days_of_rest.get(&pitcher).copied().unwrap_or(FULL_REST_DAYS)
+    }
+
+    /// Returns how much of the given pitcher's pitching is left after their rest, from 1 when fully rested down to 0.25.
+    pub fn rest_factor(&self, pitcher: PlayerId) -> f64 {
+        let days_short = FULL_REST_DAYS.saturating_sub(self.days_of_rest(pitcher));
+        (1.0 - SHORT_REST_PENALTY_PER_DAY * days_short as f64).max(0.25)
+    }
+
+    /// Gives every pitcher another day of rest.
+    pub fn pass_day(&mut self) {
+        for days in self.days_of_rest.values_mut() {
+            *days += 1;
+        }
+    }
+
+    /// Picks the starter for the next game: whoever's turn it is in the rotation, unless they are on short rest, in which case the turn goes to the next fully rested starter. If nobody is fully rested, the most rested starter goes.
+    pub fn choose_starter(&mut self) {
+        let rotation_order = (0..self.all_pitchers.len())
+            .map(|offset| self.all_pitchers[(self.rotation_index + offset) % self.all_pitchers.len()])
+            .collect::<Vec<_>>();
+        self.current_pitcher = rotation_order.iter()
+            .find(|pitcher| self.days_of_rest(**pitcher) >= FULL_REST_DAYS)
+            .or_else(|| rotation_order.iter().max_by_key(|pitcher| self.days_of_rest(**pitcher)))
+            .copied()
+            .unwrap();
+    }
+
+    /// Passes over the chosen starter in favour of the next pitcher in the rotation.
+    pub fn skip_starter(&mut self) {
+        if let Some(index) = self.all_pitchers.iter().position(|pitcher| *pitcher == self.current_pitcher) {
+            self.current_pitcher = self.all_pitchers[(index + 1) % self.all_pitchers.len()];
+        }
+    }
+
+    pub fn swap_rotation(&mut self, a: usize, b: usize) {
+        self.all_pitchers.swap(a, b);
+    }
+
+    /// Marks that the current pitcher has started a game, resetting their rest and handing the turn to whoever follows them in the rotation.
+    pub fn record_start(&mut self) {
+        self.days_of_rest.insert(self.current_pitcher, 0);
+        if let Some(index) = self.all_pitchers.iter().position(|pitcher| *pitcher == self.current_pitcher) {
+            self.rotation_index = (index + 1) % self.all_pitchers.len();
+        }
+    }
+
+    /// Checks that everyone in the lineup is still around, that nobody holds two places, that every fielder bats and that the starter comes from the rotation.
+    pub fn validate_lineup(&self, all_players: &HashMap<PlayerId, Player>) -> Result<(), LineupError> {
+        let lineup = self.batting_order.iter().chain(self.fielders.iter()).chain(std::iter::once(&self.current_pitcher));
+        for player in lineup {
+            if !all_players.contains_key(player) {
+                return Err(LineupError::UnavailablePlayer(*player));
+            }
+        }
+
+        for (i, player) in self.batting_order.iter().enumerate() {
+            if self.batting_order[..i].contains(player) {
+                return Err(LineupError::DuplicatePlayer(*player));
+            }
+        }
+        for (i, player) in self.fielders.iter().enumerate() {
+            if self.fielders[..i].contains(player) {
+                return Err(LineupError::DuplicatePlayer(*player));
+            }
+            if !self.batting_order.contains(player) {
+                return Err(LineupError::FielderNotBatting(*player));
+            }
+        }
+        if self.batting_order.contains(&self.current_pitcher) {
+            return Err(LineupError::DuplicatePlayer(self.current_pitcher));
+        }
+        if !self.all_pitchers.contains(&self.current_pitcher) {
+            return Err(LineupError::StarterNotInRotation(self.current_pitcher));
+        }
+
+        Ok(())
+    }
+
+    /// Puts `replacement` into every slot held by `player`.
+    pub fn replace_player(&mut self, player: PlayerId, replacement: PlayerId) {
+        let slots = self.batting_order.iter_mut()
+            .chain(self.fielders.iter_mut())
+            .chain(self.all_pitchers.iter_mut())
+            .chain(self.bench.iter_mut())
+            .chain(self.bullpen.iter_mut());
+        for slot in slots {
+            if *slot == player {
+                *slot = replacement;
+            }
+        }
+        if self.current_pitcher == player {
+            self.current_pitcher = replacement;
+        }
+        self.days_of_rest.remove(&player);
+    }
+}
+
+/// Rorzagoth's hold over the stadium: the side whose home it is, and how the demon currently feels about them.
+#[derive(Debug, Clone)]
+pub struct Domain {
+    pub side: TeamSide,
+    pub goodwill: f64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DemonicEventKind {
+    RainOfFrogs, // between half-innings; the hampered side's pitcher or hitters are rattled for the next half-inning
+    FlamingBall, // a ball in play bursts into flame, carrying further or burning up
+    PhasingRunner, // a runner passes through a tag, or through the bag and into the ground
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DemonicEvent {
+    pub kind: DemonicEventKind,
+    pub favored_side: TeamSide,
+    pub player: Option<PlayerId>, // the player the event happened to, if any
+}
+
+/// Returns whether rorzagoth let the given runner through a tag for the batting side, which counts as reaching on an error.
+pub fn phased_to_safety(demonic_events: &[DemonicEvent], player: PlayerId, batting_side: TeamSide) -> bool {
+    demonic_events.iter().any(|event| {
+        event.kind == DemonicEventKind::PhasingRunner && event.player == Some(player) && event.favored_side == batting_side
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HalfInning {
     pub number: u8,
     pub top: bool,
@@ -74,11 +428,19 @@ impl HalfInning {
     }
 }
 
-#[derive(Debug, Clone)]
+/// A runner on base, along with who gets charged if they score.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Runner {
+    pub player: PlayerId,
+    pub responsible_pitcher: PlayerId, // the pitcher who let them on, or whose runner they replaced on a force
+    pub earned: bool, // false if they only stayed alive because rorzagoth let them through a tag, which scores like an error
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bases {
-    pub first: Option<String>,
-    pub second: Option<String>,
-    pub third: Option<String>,
+    pub first: Option<Runner>,
+    pub second: Option<Runner>,
+    pub third: Option<Runner>,
 }
 
 impl Bases {
@@ -86,12 +448,22 @@ impl Bases {
         Self { first: None, second: None, third: None }
     }
 
+    /// Returns the runner on the given base, if there is one. Nobody stands on the batter's box or home plate.
+    pub fn runner_on(&self, base: Base) -> Option<Runner> {
+        match base {
+            Base::First => self.first,
+            Base::Second => self.second,
+            Base::Third => self.third,
+            Base::Batting | Base::Home => None,
+        }
+    }
+
     pub fn are_loaded(&self) -> bool {
         self.first.is_some() && self.second.is_some() && self.third.is_some()
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Count {
     pub balls: u8,
     pub strikes: u8,
@@ -123,6 +495,8 @@ pub struct GameState {
     pub game_outcome: GameOutcome,
     home_team_batter_index: usize,
     visiting_team_batter_index: usize,
+    mime: Option<Mime>,
+    frog_delay: Option<TeamSide>, // the side hampered by frogs for the rest of the half-inning
 }
 
 impl GameState {
@@ -138,23 +512,25 @@ impl GameState {
             game_outcome: GameOutcome::Ongoing,
             home_team_batter_index: 0,
             visiting_team_batter_index: 0,
+            mime: None,
+            frog_delay: None,
         }
     }
 }
 
-#[derive(strum::EnumIter, Copy, Clone, Debug)]
+#[derive(strum::EnumIter, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum StrikeZoneLocation {
     In,
     Out,
 }
 
-#[derive(strum::EnumIter, Copy, Clone, Debug)]
+#[derive(strum::EnumIter, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum BatterDecision {
     Swing,
     Take,
 }
 
-#[derive(strum::EnumIter, Copy, Clone, Hash, PartialEq, Eq, Debug)]
+#[derive(strum::EnumIter, Copy, Clone, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum FieldLocation {
     Close,
     Infield,
@@ -172,7 +548,7 @@ impl FieldLocation {
         }
     }
 
-    pub fn random_from_skill(&self, rng: &mut ThreadRng, skill: f64) -> Self {
+    pub fn random_from_skill(&self, rng: &mut GameRng, skill: f64) -> Self {
         if let Self::OutOfPark = self {
             *self
         } else if rng.random_bool(skill) {
@@ -183,7 +559,7 @@ impl FieldLocation {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 pub enum Base {
     Batting,
     First,
@@ -230,14 +606,14 @@ impl Base {
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct RunnerAdvancement {
-    pub name: String,
+    pub player: PlayerId,
     pub from_base: Base,
     pub to_base: Option<Base>, // if None, the runner is out
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BattingOutcome {
     Strike,
     Ball,
@@ -246,7 +622,7 @@ pub enum BattingOutcome {
     },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum AtBatOutcome {
     Strikeout,
     Walk,
@@ -257,14 +633,14 @@ pub enum AtBatOutcome {
     Out,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum GameOutcome {
     HomeTeamWins,
     VisitingTeamWins,
     Ongoing,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameStateSummary {
     pub home_team_runs: u8,
     pub visiting_team_runs: u8,
@@ -272,21 +648,49 @@ pub struct GameStateSummary {
     pub bases: Bases,
     pub outs: u8,
     pub count: Count,
-    pub batter: String,
-    pub pitcher: String,
+    pub batter: PlayerId,
+    pub pitcher: PlayerId,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EventsSummary {
     pub pitch_location: StrikeZoneLocation,
     pub batter_decision: BatterDecision,
     pub batting_outcome: BattingOutcome,
     pub at_bat_outcome: Option<AtBatOutcome>,
     pub runner_advancements: Vec<RunnerAdvancement>,
+    pub demonic_events: Vec<DemonicEvent>,
+    pub mimed_side: Option<TeamSide>, // the side walled in by a mime when the ball was put in play, if any
     pub game_outcome: GameOutcome,
 }
 
-fn random_advancement_between(from_base: Base, to_base: Base, success_prob: f64, rng: &mut ThreadRng) -> Option<Base> {
+impl EventsSummary {
+    /// Returns whether the given runner got through a tag on this pitch thanks to rorzagoth. See `phased_to_safety`.
+    pub fn phased_to_safety(&self, player: PlayerId, batting_side: TeamSide) -> bool {
+        phased_to_safety(&self.demonic_events, player, batting_side)
+    }
+}
+
+/// Something that happened in a game, kept in order so the game can be looked back over once it's done.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum GameEvent {
+    Pitch {
+        prev_game_state_summary: GameStateSummary,
+        events_summary: EventsSummary,
+        new_game_state_summary: GameStateSummary,
+    },
+    SacrificedOut {
+        prev_game_state_summary: GameStateSummary,
+        new_game_state_summary: GameStateSummary,
+    },
+    PitchingChange {
+        side: TeamSide,
+        replaced: PlayerId,
+        pitcher: PlayerId,
+    },
+}
+
+fn random_advancement_between(from_base: Base, to_base: Base, success_prob: f64, rng: &mut GameRng) -> Option<Base> {
     let max_bases = match (from_base, to_base) {
         (Base::Batting, Base::First) => 1,
         (Base::Batting, Base::Second) => 2,
@@ -302,7 +706,7 @@ fn random_advancement_between(from_base: Base, to_base: Base, success_prob: f64,
     };
 
     let mut bases = 0;
-    for _ in 0..=max_bases {
+    for _ in 0..max_bases {
         if rng.random_bool(success_prob) {
             bases += 1;
         } else {
@@ -317,52 +721,198 @@ fn random_advancement_between(from_base: Base, to_base: Base, success_prob: f64,
     }
 }
 
+/// The random number generator behind everything in the game. It's seeded, so the same seed and the same inputs always play out the same way.
+pub type GameRng = StdRng;
+
 #[derive(Debug)]
 pub struct BaseballGame {
-    rng: ThreadRng,
+    rng: GameRng,
     pub state: GameState,
-    all_players: HashMap<String, Player>,
+    all_players: HashMap<PlayerId, Player>,
     pub home_team: Team,
     pub visiting_team: Team,
+    domain: Option<Domain>, // Some if the game is played in rorzagoth's stadium
+    demonic_events: Vec<DemonicEvent>, // events that have happened during the current pitch
+    pub history: Vec<GameEvent>, // everything that has happened in the game, oldest first
 }
 
 impl BaseballGame {
-    pub fn new(all_players: HashMap<String, Player>, home_team: Team, visiting_team: Team) -> Self {
+    pub fn new(all_players: HashMap<PlayerId, Player>, home_team: Team, visiting_team: Team, rng: GameRng) -> Self {
         Self {
-            rng: rand::rng(),
+            rng,
             state: GameState::start_of_game(),
             all_players,
             home_team,
             visiting_team,
+            domain: None,
+            demonic_events: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn player(&self, player: PlayerId) -> &Player {
+        self.all_players.get(&player).unwrap()
+    }
+
+    /// Makes the stadium rorzagoth's domain, letting the demon act on their own during the game.
+    pub fn set_domain(&mut self, domain: Domain) {
+        self.domain = Some(domain);
+    }
+
+    /// Rolls whether rorzagoth acts on their own right now. Returns the side the event favors if so.
+    fn roll_demonic_event(&mut self) -> Option<TeamSide> {
+        let domain = self.domain.as_ref()?;
+        let (side, goodwill) = (domain.side, domain.goodwill);
+        if !self.rng.random_bool(demon::demonic_event_chance(goodwill)) {
+            return None;
+        }
+
+        if self.rng.random_bool(goodwill.clamp(0.0, 1.0)) {
+            Some(side)
+        } else {
+            Some(side.other())
         }
     }
 
+    /// Lets a ball in play burst into flame. It carries further if the batting side is favored, and burns up if the fielding side is.
+    fn maybe_set_ball_alight(&mut self, field_location: FieldLocation) -> FieldLocation {
+        if let FieldLocation::OutOfPark = field_location {
+            return field_location;
+        }
+        let Some(favored_side) = self.roll_demonic_event() else {
+            return field_location;
+        };
+
+        self.demonic_events.push(DemonicEvent {
+            kind: DemonicEventKind::FlamingBall,
+            favored_side,
+            player: None,
+        });
+        if favored_side == self.batting_side() {
+            field_location.next()
+        } else {
+            FieldLocation::Close
+        }
+    }
+
+    /// Lets a runner pass through a tag. A favored runner who would have been out is safe at the next base, and a disfavored runner who would have been safe sinks through the bag and is out.
+    fn maybe_phase_runner(&mut self, runner_advancements: &mut [RunnerAdvancement]) {
+        let Some(favored_side) = self.roll_demonic_event() else {
+            return;
+        };
+
+        let phased = if favored_side == self.batting_side() {
+            let mut occupied_bases = runner_advancements.iter().filter_map(|advancement| advancement.to_base).collect::<Vec<_>>();
+            for (base, runner) in [(Base::First, &self.state.bases.first), (Base::Second, &self.state.bases.second), (Base::Third, &self.state.bases.third)] {
+                let runner_holds = runner.is_some() && !runner_advancements.iter().any(|advancement| advancement.from_base == base);
+                if runner_holds {
+                    occupied_bases.push(base);
+                }
+            }
+            runner_advancements.iter_mut().find(|advancement| {
+                let next_base = advancement.from_base.next().unwrap();
+                advancement.from_base != Base::Batting
+                    && advancement.to_base.is_none()
+                    && (next_base == Base::Home || !occupied_bases.contains(&next_base))
+            }).map(|advancement| {
+                advancement.to_base = advancement.from_base.next();
+                advancement.player
+            })
+        } else {
+            runner_advancements.iter_mut().find(|advancement| {
+                advancement.from_base != Base::Batting && advancement.to_base.is_some_and(|base| base != Base::Home)
+            }).map(|advancement| {
+                advancement.to_base = None;
+                advancement.player
+            })
+        };
+
+        if let Some(player) = phased {
+            self.demonic_events.push(DemonicEvent {
+                kind: DemonicEventKind::PhasingRunner,
+                favored_side,
+                player: Some(player),
+            });
+        }
+    }
+
+    /// Lets frogs rain on the field between half-innings, rattling the disfavored side for the half-inning to come.
+    fn maybe_rain_frogs(&mut self) {
+        let Some(favored_side) = self.roll_demonic_event() else {
+            return;
+        };
+
+        self.state.frog_delay = Some(favored_side.other());
+        self.demonic_events.push(DemonicEvent {
+            kind: DemonicEventKind::RainOfFrogs,
+            favored_side,
+            player: None,
+        });
+    }
+
     pub fn home_team_is_at_bat(&self) -> bool {
         !self.state.half_inning.top
     }
 
-    fn walk_advancements(&self, batter_name: &String) -> Vec<RunnerAdvancement> {
+    pub fn batting_side(&self) -> TeamSide {
+        if self.home_team_is_at_bat() { TeamSide::Home } else { TeamSide::Visiting }
+    }
+
+    pub fn fielding_side(&self) -> TeamSide {
+        self.batting_side().other()
+    }
+
+    pub fn team(&self, side: TeamSide) -> &Team {
+        match side {
+            TeamSide::Home => &self.home_team,
+            TeamSide::Visiting => &self.visiting_team,
+        }
+    }
+
+    pub fn mime(&self) -> Option<&Mime> {
+        self.state.mime.as_ref()
+    }
+
+    /// Surrounds every player on the given side with invisible walls for the given number of balls in play.
+    pub fn place_invisible_walls(&mut self, side: TeamSide, plays: u8) {
+        self.state.mime = Some(Mime { side, plays_remaining: plays });
+    }
+
+    fn side_is_mimed(&self, side: TeamSide) -> bool {
+        self.state.mime.as_ref().is_some_and(|mime| mime.side == side)
+    }
+
+    fn wear_down_invisible_walls(&mut self) {
+        if let Some(mime) = self.state.mime.as_mut() {
+            mime.plays_remaining = mime.plays_remaining.saturating_sub(1);
+            if mime.plays_remaining == 0 {
+                self.state.mime = None;
+            }
+        }
+    }
+
+    fn walk_advancements(&self, batter: PlayerId) -> Vec<RunnerAdvancement> {
         let mut runner_advancements = Vec::new();
         runner_advancements.push(RunnerAdvancement {
-            name: batter_name.clone(),
+            player: batter,
             from_base: Base::Batting,
             to_base: Some(Base::First),
         });
-        if let Some(runner) = self.state.bases.first.clone() {
+        if let Some(runner) = self.state.bases.first {
             runner_advancements.push(RunnerAdvancement {
-                name: runner,
+                player: runner.player,
                 from_base: Base::First,
                 to_base: Some(Base::Second),
             });
-            if let Some(runner) = self.state.bases.second.clone() {
+            if let Some(runner) = self.state.bases.second {
                 runner_advancements.push(RunnerAdvancement {
-                    name: runner,
+                    player: runner.player,
                     from_base: Base::Second,
                     to_base: Some(Base::Third),
                 });
-                if let Some(runner) = self.state.bases.third.clone() {
+                if let Some(runner) = self.state.bases.third {
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Third,
                         to_base: Some(Base::Home),
                     });
@@ -373,14 +923,40 @@ impl BaseballGame {
         runner_advancements
     }
 
-    fn apply_runner_advancements(&mut self, runner_advancements: &mut Vec<RunnerAdvancement>) {
+    /// Moves every runner where the play sent them. Lead runners go first, so nobody is moved onto a base before its runner has left it.
+    fn apply_runner_advancements(&mut self, runner_advancements: &[RunnerAdvancement]) {
         if runner_advancements.is_empty() {
             return;
         }
-        let mut runner_advancements_sorted = runner_advancements.clone();
-        runner_advancements_sorted.sort_by_key(|advancement| advancement.from_base);
+        let mut runner_advancements_sorted = runner_advancements.to_vec();
+        runner_advancements_sorted.sort_by_key(|advancement| std::cmp::Reverse(advancement.from_base));
 
+        let pitcher = self.team(self.fielding_side()).current_pitcher;
+        let batting_side = self.batting_side();
+        let mut forced_out_responsibility = None; // a previous pitcher's runner put out on the play, whose place on that pitcher's account the batter takes
         for advancement in runner_advancements_sorted {
+            let mut runner = match advancement.from_base {
+                Base::Batting => {
+                    if self.state.half_inning.top {
+                        self.state.visiting_team_batter_index = (self.state.visiting_team_batter_index + 1) % 9;
+                    } else {
+                        self.state.home_team_batter_index = (self.state.home_team_batter_index + 1) % 9;
+                    }
+                    Runner {
+                        player: advancement.player,
+                        responsible_pitcher: forced_out_responsibility.take().unwrap_or(pitcher),
+                        earned: true,
+                    }
+                },
+                Base::First => self.state.bases.first.take().unwrap(),
+                Base::Second => self.state.bases.second.take().unwrap(),
+                Base::Third => self.state.bases.third.take().unwrap(),
+                Base::Home => unreachable!(),
+            };
+            if phased_to_safety(&self.demonic_events, advancement.player, batting_side) {
+                runner.earned = false;
+            }
+
             match advancement.to_base {
                 Some(Base::Home) => {
                     if self.state.half_inning.top {
@@ -389,37 +965,38 @@ impl BaseballGame {
                         self.state.home_team_runs += 1;
                     }
                 },
-                Some(Base::Third) => self.state.bases.third = Some(advancement.name.clone()),
-                Some(Base::Second) => self.state.bases.second = Some(advancement.name.clone()),
-                Some(Base::First) => self.state.bases.first = Some(advancement.name.clone()),
+                Some(Base::Third) => self.state.bases.third = Some(runner),
+                Some(Base::Second) => self.state.bases.second = Some(runner),
+                Some(Base::First) => self.state.bases.first = Some(runner),
                 Some(Base::Batting) => unreachable!(),
-                None => self.state.outs += 1,
-            }
-
-            match advancement.from_base {
-                Base::Batting => if self.state.half_inning.top {
-                    self.state.visiting_team_batter_index = (self.state.visiting_team_batter_index + 1) % 9;
-                } else {
-                    self.state.home_team_batter_index = (self.state.home_team_batter_index + 1) % 9;
+                None => {
+                    self.state.outs += 1;
+                    if runner.responsible_pitcher != pitcher && advancement.from_base != Base::Batting {
+                        forced_out_responsibility = Some(runner.responsible_pitcher);
+                    }
                 },
-                Base::First => self.state.bases.first = None,
-                Base::Second => self.state.bases.second = None,
-                Base::Third => self.state.bases.third = None,
-                Base::Home => unreachable!(),
             }
         }
     }
 
+    /// Brings a new pitcher into the game for the given side. They take over from the next pitch, and runners already on base stay charged to whoever let them on.
+    pub fn change_pitcher(&mut self, side: TeamSide, pitcher: PlayerId) {
+        let team = match side {
+            TeamSide::Home => &mut self.home_team,
+            TeamSide::Visiting => &mut self.visiting_team,
+        };
+        let replaced = std::mem::replace(&mut team.current_pitcher, pitcher);
+        self.history.push(GameEvent::PitchingChange { side, replaced, pitcher });
+    }
+
     fn game_outcome(&self) -> GameOutcome {
         // first 8 innings
         if self.state.half_inning.number < 9 {
             return GameOutcome::Ongoing;
         }
-        // walk-offs
-        if self.state.half_inning.number == 9 && !self.state.half_inning.top {
-            if self.state.home_team_runs > self.state.visiting_team_runs {
-                return GameOutcome::HomeTeamWins;
-            }
+        // walk-offs, or the home team leading after the top of the 9th or later
+        if !self.state.half_inning.top && self.state.home_team_runs > self.state.visiting_team_runs {
+            return GameOutcome::HomeTeamWins;
         }
         // extra innings
         if self.state.half_inning.number > 9 && self.state.half_inning.top && self.state.visiting_team_runs > self.state.home_team_runs && self.state.last_inning_just_ended {
@@ -428,38 +1005,42 @@ impl BaseballGame {
         GameOutcome::Ongoing
     }
 
-    fn simulate_fielding_and_running(&mut self, batter_name: &String, field_location: FieldLocation) -> (AtBatOutcome, Vec<RunnerAdvancement>) {
+    fn simulate_fielding_and_running(&mut self, batter: PlayerId, field_location: FieldLocation) -> (AtBatOutcome, Vec<RunnerAdvancement>) {
+        let fielders_mimed = self.side_is_mimed(self.fielding_side());
+        let runners_mimed = self.side_is_mimed(self.batting_side());
+        self.wear_down_invisible_walls();
+
         // handle home run
         if let FieldLocation::OutOfPark = field_location {
             let mut runner_advancements = Vec::new();
             runner_advancements.push(RunnerAdvancement {
-                name: batter_name.clone(),
+                player: batter,
                 from_base: Base::Batting,
                 to_base: Some(Base::Home),
             });
-            if let Some(runner) = self.state.bases.third.clone() {
+            if let Some(runner) = self.state.bases.third {
                 runner_advancements.push(RunnerAdvancement {
-                    name: runner,
+                    player: runner.player,
                     from_base: Base::Third,
                     to_base: Some(Base::Home),
                 });
             }
-            if let Some(runner) = self.state.bases.second.clone() {
+            if let Some(runner) = self.state.bases.second {
                 runner_advancements.push(RunnerAdvancement {
-                    name: runner,
+                    player: runner.player,
                     from_base: Base::Second,
                     to_base: Some(Base::Home),
                 });
             }
-            if let Some(runner) = self.state.bases.first.clone() {
+            if let Some(runner) = self.state.bases.first {
                 runner_advancements.push(RunnerAdvancement {
-                    name: runner,
+                    player: runner.player,
                     from_base: Base::First,
                     to_base: Some(Base::Home),
                 });
             }
 
-            self.apply_runner_advancements(&mut runner_advancements);
+            self.apply_runner_advancements(&runner_advancements);
 
             return (AtBatOutcome::HomeRun, runner_advancements);
         }
@@ -468,51 +1049,56 @@ impl BaseballGame {
         let second_forced = self.state.bases.first.is_some() && self.state.bases.second.is_some();
         let third_forced = self.state.bases.first.is_some() && self.state.bases.second.is_some() && self.state.bases.third.is_some();
 
-        let distance_difficulty: f64 = match field_location {
+        let mut distance_difficulty: f64 = match field_location {
             FieldLocation::Close => Level::High,
             FieldLocation::Infield => Level::Medium,
             FieldLocation::Outfield => Level::Low,
             FieldLocation::OutOfPark => unreachable!(),
         }.into();
-        let mean_fielder_skill = self.visiting_team.fielders.iter().map(|fielder| {
+        let fielders = &self.team(self.fielding_side()).fielders;
+        let mut mean_fielder_skill = fielders.iter().map(|fielder| {
             let fielder_skill: f64 = self.all_players.get(fielder).unwrap().metrics.fielding.into();
             fielder_skill
-        }).sum::<f64>() / self.visiting_team.fielders.len() as f64;
+        }).sum::<f64>() / fielders.len() as f64;
+        if fielders_mimed { // fielders can't move, so the ball only has to get past wherever they happen to be standing
+            distance_difficulty /= 4.0;
+            mean_fielder_skill = Level::VeryLow.into();
+        }
         let overall_difficulty = distance_difficulty + mean_fielder_skill;
 
         let mut runner_advancements = Vec::new();
         let mut furthest_occupied = Base::Home;
 
         // advance runners
-        if let Some(runner) = self.state.bases.third.clone() {
-            let runner_skill: f64 = self.all_players.get(&runner).unwrap().metrics.running.into();
+        if let Some(runner) = self.state.bases.third {
+            let runner_skill: f64 = self.all_players.get(&runner.player).unwrap().metrics.running.into();
             let success_prob = runner_skill / (runner_skill + overall_difficulty);
 
             if third_forced { // runner is forced to advance
                 if self.rng.random_bool(success_prob) {
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Third,
                         to_base: Some(Base::Home),
                     });
                 } else { // force out
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Third,
                         to_base: None,
                     });
                     furthest_occupied = Base::Third;
                 }
-            } else if success_prob >= 0.5 { // runner may choose to advance if success probability is high enough
+            } else if success_prob >= 0.5 && !runners_mimed { // runner may choose to advance if success probability is high enough
                 if self.rng.random_bool(success_prob) {
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Third,
                         to_base: Some(Base::Home),
                     });
                 } else {
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Third,
                         to_base: None,
                     });
@@ -523,32 +1109,28 @@ impl BaseballGame {
             }
         }
 
-        if let Some(runner) = self.state.bases.second.clone() {
-            let target_base = furthest_occupied.prev().unwrap();
+        if let Some(runner) = self.state.bases.second {
+            let mut target_base = furthest_occupied.prev().unwrap();
+            if runners_mimed { // walled-in runners can only shuffle forward one base
+                target_base = target_base.min(Base::Third);
+            }
             if target_base <= Base::Second { // no room to advance
                 if second_forced { // force out
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Second,
                         to_base: None,
                     });
                 }
                 furthest_occupied = furthest_occupied.min(Base::Second);
             } else {
-                let runner_skill: f64 = self.all_players.get(&runner).unwrap().metrics.running.into();
+                let runner_skill: f64 = self.all_players.get(&runner.player).unwrap().metrics.running.into();
                 let success_prob = runner_skill / (runner_skill + overall_difficulty);
 
                 let to_base = random_advancement_between(Base::Second, target_base, success_prob, &mut self.rng);
-                if second_forced {
+                if second_forced || (success_prob >= 0.5 && !runners_mimed) { // runner may choose to advance if success probability is high enough
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
-                        from_base: Base::Second,
-                        to_base,
-                    });
-                    furthest_occupied = to_base.unwrap_or(Base::Second);
-                } else if success_prob >= 0.5 {
-                    runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::Second,
                         to_base,
                     });
@@ -559,32 +1141,28 @@ impl BaseballGame {
             }
         }
 
-        if let Some(runner) = self.state.bases.first.clone() {
-            let target_base = furthest_occupied.prev().unwrap();
+        if let Some(runner) = self.state.bases.first {
+            let mut target_base = furthest_occupied.prev().unwrap();
+            if runners_mimed { // walled-in runners can only shuffle forward one base
+                target_base = target_base.min(Base::Second);
+            }
             if target_base <= Base::First { // no room to advance
                 if first_forced { // force out
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::First,
                         to_base: None,
                     });
                 }
                 furthest_occupied = furthest_occupied.min(Base::First);
             } else {
-                let runner_skill: f64 = self.all_players.get(&runner).unwrap().metrics.running.into();
+                let runner_skill: f64 = self.all_players.get(&runner.player).unwrap().metrics.running.into();
                 let success_prob = runner_skill / (runner_skill + overall_difficulty);
 
                 let to_base = random_advancement_between(Base::First, target_base, success_prob, &mut self.rng);
-                if first_forced {
-                    runner_advancements.push(RunnerAdvancement {
-                        name: runner,
-                        from_base: Base::First,
-                        to_base,
-                    });
-                    furthest_occupied = to_base.unwrap_or(Base::First);
-                } else if success_prob >= 0.5 {
+                if first_forced || (success_prob >= 0.5 && !runners_mimed) { // runner may choose to advance if success probability is high enough
                     runner_advancements.push(RunnerAdvancement {
-                        name: runner,
+                        player: runner.player,
                         from_base: Base::First,
                         to_base,
                     });
@@ -596,22 +1174,25 @@ impl BaseballGame {
         }
 
         // advance batter
-        let target_base = furthest_occupied.prev().unwrap();
+        let mut target_base = furthest_occupied.prev().unwrap();
+        if runners_mimed {
+            target_base = target_base.min(Base::First);
+        }
         let at_bat_outcome = if target_base < Base::First { // no room to advance, automatically out
             runner_advancements.push(RunnerAdvancement {
-                name: batter_name.clone(),
+                player: batter,
                 from_base: Base::Batting,
                 to_base: None,
             });
 
             AtBatOutcome::Out
         } else {
-            let batter_skill: f64 = self.all_players.get(batter_name).unwrap().metrics.running.into();
+            let batter_skill: f64 = self.all_players.get(&batter).unwrap().metrics.running.into();
             let success_prob = batter_skill / (batter_skill + overall_difficulty);
 
             let to_base = random_advancement_between(Base::Batting, target_base, success_prob, &mut self.rng);
             runner_advancements.push(RunnerAdvancement {
-                name: batter_name.clone(),
+                player: batter,
                 from_base: Base::Batting,
                 to_base,
             });
@@ -626,7 +1207,8 @@ impl BaseballGame {
             }
         };
 
-        self.apply_runner_advancements(&mut runner_advancements);
+        self.maybe_phase_runner(&mut runner_advancements);
+        self.apply_runner_advancements(&runner_advancements);
 
         (at_bat_outcome, runner_advancements)
     }
@@ -637,6 +1219,7 @@ impl BaseballGame {
         self.state.outs = 0;
         self.state.count = Count::empty();
         self.state.last_inning_just_ended = true;
+        self.state.frog_delay = None;
     }
 
     pub fn simulate_pitch(
@@ -652,15 +1235,20 @@ impl BaseballGame {
         // 6. return a summary of what has happened
 
         self.state.last_inning_just_ended = false;
+        let prev_game_state_summary = self.state_summary();
 
-        let pitcher_name = if self.home_team_is_at_bat() {
-            self.visiting_team.current_pitcher.clone()
+        let pitcher = if self.home_team_is_at_bat() {
+            self.visiting_team.current_pitcher
         } else {
-            self.home_team.current_pitcher.clone()
+            self.home_team.current_pitcher
         };
 
         let pitch_aim_location = pitch_aim_location.unwrap_or(StrikeZoneLocation::iter().choose(&mut self.rng).unwrap());
-        let pitcher_skill: f64 = self.all_players.get(&pitcher_name).unwrap().metrics.pitching.into();
+        let mut pitcher_skill: f64 = self.all_players.get(&pitcher).unwrap().metrics.pitching.into();
+        pitcher_skill *= self.team(self.fielding_side()).rest_factor(pitcher);
+        if self.state.frog_delay == Some(self.fielding_side()) {
+            pitcher_skill /= 2.0;
+        }
         let pitcher_succeeds = self.rng.random_bool(pitcher_skill);
         let pitch_location = match (pitch_aim_location, pitcher_succeeds) {
             (StrikeZoneLocation::In, true) => StrikeZoneLocation::In,
@@ -669,20 +1257,25 @@ impl BaseballGame {
             (StrikeZoneLocation::Out, false) => StrikeZoneLocation::In,
         };
 
-        let batter_name = if self.home_team_is_at_bat() {
-            self.home_team.batting_order[self.state.home_team_batter_index].clone()
+        let batter = if self.home_team_is_at_bat() {
+            self.home_team.batting_order[self.state.home_team_batter_index]
         } else {
-            self.visiting_team.batting_order[self.state.visiting_team_batter_index].clone()
+            self.visiting_team.batting_order[self.state.visiting_team_batter_index]
         };
 
         let batter_decision = batter_decision.unwrap_or(BatterDecision::iter().choose(&mut self.rng).unwrap());
-        let batter_skill: f64 = self.all_players.get(&batter_name).unwrap().metrics.hitting.into();
+        let mut batter_skill: f64 = self.all_players.get(&batter).unwrap().metrics.hitting.into();
+        if self.state.frog_delay == Some(self.batting_side()) {
+            batter_skill /= 2.0;
+        }
         let mut events_summary = match (pitch_location, batter_decision) {
             (_, BatterDecision::Swing) => {
                 let contact = self.rng.random_bool(batter_skill);
                 if contact { // swing and contact
                     let field_location = FieldLocation::Close.random_from_skill(&mut self.rng, batter_skill);
-                    let (at_bat_outcome, runner_advancements) = self.simulate_fielding_and_running(&batter_name, field_location);
+                    let field_location = self.maybe_set_ball_alight(field_location);
+                    let mimed_side = self.mime().map(|mime| mime.side);
+                    let (at_bat_outcome, runner_advancements) = self.simulate_fielding_and_running(batter, field_location);
                     self.state.count = Count::empty();
 
                     EventsSummary {
@@ -691,6 +1284,8 @@ impl BaseballGame {
                         batting_outcome: BattingOutcome::Hit { to_field_location: field_location },
                         at_bat_outcome: Some(at_bat_outcome),
                         runner_advancements,
+                        demonic_events: Vec::new(),
+                        mimed_side,
                         game_outcome: GameOutcome::Ongoing,
                     }
                 } else { // swing and miss
@@ -702,6 +1297,8 @@ impl BaseballGame {
                         batting_outcome: BattingOutcome::Strike,
                         at_bat_outcome: None,
                         runner_advancements: Vec::new(),
+                        demonic_events: Vec::new(),
+                        mimed_side: None,
                         game_outcome: GameOutcome::Ongoing,
                     }
                 }
@@ -715,6 +1312,8 @@ impl BaseballGame {
                     batting_outcome: BattingOutcome::Strike,
                     at_bat_outcome: None,
                     runner_advancements: Vec::new(),
+                    demonic_events: Vec::new(),
+                    mimed_side: None,
                     game_outcome: GameOutcome::Ongoing,
                 }
             },
@@ -727,6 +1326,8 @@ impl BaseballGame {
                     batting_outcome: BattingOutcome::Ball,
                     at_bat_outcome: None,
                     runner_advancements: Vec::new(),
+                    demonic_events: Vec::new(),
+                    mimed_side: None,
                     game_outcome: GameOutcome::Ongoing,
                 }
             },
@@ -745,8 +1346,8 @@ impl BaseballGame {
         } else if self.state.count.balls == 4 {
             events_summary.at_bat_outcome = Some(AtBatOutcome::Walk);
             self.state.count = Count::empty();
-            let mut walk_advancements = self.walk_advancements(&batter_name);
-            self.apply_runner_advancements(&mut walk_advancements); // this also cycles the batter
+            let walk_advancements = self.walk_advancements(batter);
+            self.apply_runner_advancements(&walk_advancements); // this also cycles the batter
             events_summary.runner_advancements = walk_advancements;
         }
 
@@ -756,21 +1357,68 @@ impl BaseballGame {
         }
 
         self.state.game_outcome = self.game_outcome();
-        events_summary.game_outcome = self.state.game_outcome.clone();
+        events_summary.game_outcome = self.state.game_outcome;
+
+        if self.state.last_inning_just_ended && self.state.game_outcome == GameOutcome::Ongoing {
+            self.maybe_rain_frogs();
+        }
+        events_summary.demonic_events = std::mem::take(&mut self.demonic_events);
 
+        self.history.push(GameEvent::Pitch {
+            prev_game_state_summary,
+            events_summary: events_summary.clone(),
+            new_game_state_summary: self.state_summary(),
+        });
         events_summary
     }
 
+    /// Gives up an out for the batting side without a pitch being thrown. The current batter is retired.
+    pub fn sacrifice_out(&mut self) {
+        self.state.last_inning_just_ended = false;
+        let prev_game_state_summary = self.state_summary();
+        self.state.outs += 1;
+        self.state.count = Count::empty();
+        if self.state.half_inning.top {
+            self.state.visiting_team_batter_index = (self.state.visiting_team_batter_index + 1) % 9;
+        } else {
+            self.state.home_team_batter_index = (self.state.home_team_batter_index + 1) % 9;
+        }
+
+        if self.state.outs >= 3 {
+            self.cycle_half_inning();
+        }
+        self.state.game_outcome = self.game_outcome();
+
+        self.history.push(GameEvent::SacrificedOut {
+            prev_game_state_summary,
+            new_game_state_summary: self.state_summary(),
+        });
+    }
+
+    /// Plays the rest of the game with random decisions on both sides. See `sim::simulate_game` for a game managed like a real one, with a box score.
+    pub fn simulate_to_end(&mut self) {
+        while self.state.game_outcome == GameOutcome::Ongoing {
+            self.simulate_pitch(None, None);
+        }
+    }
+
+    pub fn result(&self) -> GameResult {
+        GameResult {
+            home_team_runs: self.state.home_team_runs,
+            visiting_team_runs: self.state.visiting_team_runs,
+        }
+    }
+
     pub fn state_summary(&self) -> GameStateSummary {
-        let batter_name = if self.state.half_inning.top {
-            self.visiting_team.batting_order[self.state.visiting_team_batter_index].clone()
+        let batter = if self.state.half_inning.top {
+            self.visiting_team.batting_order[self.state.visiting_team_batter_index]
         } else {
-            self.home_team.batting_order[self.state.home_team_batter_index].clone()
+            self.home_team.batting_order[self.state.home_team_batter_index]
         };
-        let pitcher_name = if self.state.half_inning.top {
-            self.home_team.current_pitcher.clone()
+        let pitcher = if self.state.half_inning.top {
+            self.home_team.current_pitcher
         } else {
-            self.visiting_team.current_pitcher.clone()
+            self.visiting_team.current_pitcher
         };
 
         GameStateSummary {
@@ -780,8 +1428,204 @@ impl BaseballGame {
             bases: self.state.bases.clone(),
             outs: self.state.outs,
             count: self.state.count.clone(),
-            batter: batter_name,
-            pitcher: pitcher_name,
+            batter,
+            pitcher,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{advance, players, runner, team};
+
+    /// Returns a game in the top of the first, with the home starter `PlayerId(9)` pitching to visitors numbered from 100.
+    fn game() -> BaseballGame {
+        BaseballGame::new(HashMap::new(), team("home", 0), team("visiting", 100), GameRng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn walled_in_runners_only_move_up_a_base_and_nobody_overruns() {
+        let bases = [(true, false, false), (false, true, false), (true, true, false), (true, true, true), (false, true, true)];
+        let field_locations = [FieldLocation::Close, FieldLocation::Infield, FieldLocation::Outfield];
+        for (seed, (first, second, third)) in (0..200).zip(bases.iter().cycle()) {
+            for (mimed_side, field_location) in [TeamSide::Visiting, TeamSide::Home].into_iter().flat_map(|side| field_locations.map(|location| (side, location))) {
+                let mut game = BaseballGame::new(players(), team("home", 0), team("visiting", 100), GameRng::seed_from_u64(seed));
+                game.state.bases = Bases {
+                    first: if *first { runner(101, 9) } else { None },
+                    second: if *second { runner(102, 9) } else { None },
+                    third: if *third { runner(103, 9) } else { None },
+                };
+                game.place_invisible_walls(mimed_side, 1);
+                let (_, runner_advancements) = game.simulate_fielding_and_running(PlayerId(100), field_location);
+
+                let reached = runner_advancements.iter().filter_map(|advancement| advancement.to_base).filter(|base| *base != Base::Home).collect::<Vec<_>>();
+                assert!(reached.iter().enumerate().all(|(i, base)| !reached[..i].contains(base)), "runners share a base: {runner_advancements:?}");
+                if mimed_side == TeamSide::Visiting {
+                    for advancement in runner_advancements.iter() {
+                        assert!(advancement.to_base <= advancement.from_base.next(), "walled-in runner went too far: {advancement:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walk_with_runner_on_first_keeps_both_runners() {
+        let mut game = game();
+        game.state.bases.first = runner(100, 9);
+        game.apply_runner_advancements(&[advance(101, Base::Batting, Some(Base::First)), advance(100, Base::First, Some(Base::Second))]);
+
+        assert_eq!(game.state.bases.first.unwrap().player, PlayerId(101));
+        assert_eq!(game.state.bases.second.unwrap().player, PlayerId(100));
+    }
+
+    #[test]
+    fn batter_takes_over_inherited_runner_forced_out() {
+        let mut game = game();
+        game.state.bases.first = runner(100, 9);
+        game.change_pitcher(TeamSide::Home, PlayerId(10));
+        game.apply_runner_advancements(&[advance(101, Base::Batting, Some(Base::First)), advance(100, Base::First, None)]);
+
+        let batter = game.state.bases.first.unwrap();
+        assert_eq!(batter.player, PlayerId(101));
+        assert_eq!(batter.responsible_pitcher, PlayerId(9));
+        assert_eq!(game.state.outs, 1);
+    }
+
+    #[test]
+    fn new_batters_are_charged_to_the_new_pitcher() {
+        let mut game = game();
+        game.change_pitcher(TeamSide::Home, PlayerId(10));
+        game.apply_runner_advancements(&[advance(101, Base::Batting, Some(Base::First))]);
+
+        assert_eq!(game.state.bases.first.unwrap().responsible_pitcher, PlayerId(10));
+    }
+
+    #[test]
+    fn runner_phased_to_safety_is_unearned() {
+        let mut game = game();
+        game.state.bases.first = runner(100, 9);
+        game.demonic_events.push(DemonicEvent {
+            kind: DemonicEventKind::PhasingRunner,
+            favored_side: TeamSide::Visiting,
+            player: Some(PlayerId(100)),
+        });
+        game.apply_runner_advancements(&[advance(100, Base::First, Some(Base::Second))]);
+
+        assert!(!game.state.bases.second.unwrap().earned);
+    }
+
+    #[test]
+    fn home_side_leading_after_the_top_of_the_ninth_wins() {
+        let end_top_half = |number: u8, home_team_runs: u8| {
+            let mut game = game();
+            game.state.half_inning = HalfInning { number, top: true };
+            game.state.home_team_runs = home_team_runs;
+            game.state.visiting_team_runs = 1;
+            game.state.outs = 2;
+            game.sacrifice_out();
+            game.state.game_outcome
+        };
+
+        assert_eq!(end_top_half(9, 2), GameOutcome::HomeTeamWins);
+        assert_eq!(end_top_half(9, 1), GameOutcome::Ongoing);
+        assert_eq!(end_top_half(8, 2), GameOutcome::Ongoing);
+    }
+
+    #[test]
+    fn lineup_must_be_whole_and_on_hand() {
+        let all_players = crate::test_support::players();
+        let mut team = team("home", 0);
+        assert_eq!(team.validate_lineup(&all_players), Ok(()));
+
+        team.batting_order[1] = PlayerId(2);
+        assert_eq!(team.validate_lineup(&all_players), Err(LineupError::DuplicatePlayer(PlayerId(2))));
+
+        let mut team = team_with_bench();
+        team.fielders[0] = PlayerId(12);
+        assert_eq!(team.validate_lineup(&all_players), Err(LineupError::UnavailablePlayer(PlayerId(12))));
+        team.fielders[0] = PlayerId(11);
+        assert_eq!(team.validate_lineup(&all_players), Err(LineupError::FielderNotBatting(PlayerId(11))));
+
+        let mut team = team_with_bench();
+        team.current_pitcher = PlayerId(10);
+        assert_eq!(team.validate_lineup(&all_players), Err(LineupError::StarterNotInRotation(PlayerId(10))));
+        team.current_pitcher = PlayerId(0);
+        team.all_pitchers.push(PlayerId(0));
+        assert_eq!(team.validate_lineup(&all_players), Err(LineupError::DuplicatePlayer(PlayerId(0))));
+    }
+
+    /// Returns a team from `team` with `PlayerId(11)` moved from the bullpen to the bench.
+    fn team_with_bench() -> Team {
+        let mut team = team("home", 0);
+        team.bullpen.retain(|player| *player != PlayerId(11));
+        team.bench.push(PlayerId(11));
+        team
+    }
+
+    #[test]
+    fn assigning_a_fielder_swaps_them_in_without_losing_anyone() {
+        let all_players = crate::test_support::players();
+        let mut team = team_with_bench();
+        let first_base = Position::FirstBase;
+        let second_base = Position::SecondBase;
+
+        // two fielders trade places
+        team.assign_fielder(first_base, PlayerId(3));
+        assert_eq!((team.fielders[1], team.fielders[2]), (PlayerId(3), PlayerId(2)));
+
+        // the designated hitter takes the field and the displaced fielder becomes the designated hitter
+        team.assign_fielder(second_base, PlayerId(0));
+        assert_eq!(team.fielders[2], PlayerId(0));
+        assert_eq!(team.designated_hitter(), Some(PlayerId(2)));
+
+        // a bench player takes the displaced fielder's spot in the batting order
+        team.assign_fielder(first_base, PlayerId(11));
+        assert_eq!(team.fielders[1], PlayerId(11));
+        assert_eq!(team.batting_order[3], PlayerId(11));
+        assert_eq!(team.bench, [PlayerId(3)]);
+
+        assert_eq!(team.validate_lineup(&all_players), Ok(()));
+    }
+
+    /// Returns the starters chosen for the given number of games on consecutive days.
+    fn starts(team: &mut Team, games: usize) -> Vec<u32> {
+        let mut starters = Vec::new();
+        for _ in 0..games {
+            team.choose_starter();
+            starters.push(team.current_pitcher.0);
+            team.record_start();
+            team.pass_day();
         }
+        starters
+    }
+
+    #[test]
+    fn rotation_goes_in_order_while_everyone_is_rested() {
+        let mut team = team("home", 0);
+        team.all_pitchers = (9..14).map(PlayerId).collect();
+        assert_eq!(starts(&mut team, 7), [9, 10, 11, 12, 13, 9, 10]);
+        assert_eq!(team.rest_factor(PlayerId(11)), 1.0);
+
+        // a starter on short rest is passed over for the next rested one
+        team.days_of_rest.insert(PlayerId(11), 2);
+        assert_eq!(starts(&mut team, 1), [12]);
+    }
+
+    #[test]
+    fn short_rotation_pitches_on_short_rest() {
+        let mut team = team("home", 0);
+        team.all_pitchers = (9..12).map(PlayerId).collect();
+        assert_eq!(starts(&mut team, 4), [9, 10, 11, 9]);
+        assert_eq!(team.days_of_rest(PlayerId(9)), 1);
+
+        let mut rest_factor = |days| {
+            team.days_of_rest.insert(PlayerId(9), days);
+            team.rest_factor(PlayerId(9))
+        };
+        assert!((rest_factor(3) - 0.85).abs() < 1e-9);
+        assert!((rest_factor(0) - 0.4).abs() < 1e-9);
+        assert_eq!(rest_factor(FULL_REST_DAYS + 3), 1.0);
     }
 }
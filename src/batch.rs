@@ -0,0 +1,185 @@
+use crate::analytics::RunExpectancy;
+use crate::baseball::{GameRng, Player, PlayerId, Team};
+use crate::sim::{self, SimulatedGame};
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::thread;
+
+const REGULATION_INNINGS: usize = 9;
+
+/// A league-wide rate measured over a batch of simulated games, for checking the simulation against real baseball.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BatchStat {
+    BattingAverage,
+    WalkRate,
+    StrikeoutRate,
+    HomeRunRate,
+    RunsPerGame,
+    ExtraInnings,
+}
+
+impl BatchStat {
+    pub const ALL: [BatchStat; 6] = [
+        BatchStat::BattingAverage,
+        BatchStat::WalkRate,
+        BatchStat::StrikeoutRate,
+        BatchStat::HomeRunRate,
+        BatchStat::RunsPerGame,
+        BatchStat::ExtraInnings,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchStat::BattingAverage => "Batting average",
+            BatchStat::WalkRate => "Walk rate",
+            BatchStat::StrikeoutRate => "Strikeout rate",
+            BatchStat::HomeRunRate => "Home run rate",
+            BatchStat::RunsPerGame => "Runs per game",
+            BatchStat::ExtraInnings => "Extra innings",
+        }
+    }
+
+    /// Returns the range the major leagues have landed in over recent seasons. Walk, strikeout and home run rates are per plate appearance, runs are per team per game and extra innings are the share of games that needed them.
+    pub fn mlb_range(&self) -> RangeInclusive<f64> {
+        match self {
+            BatchStat::BattingAverage => 0.240..=0.260,
+            BatchStat::WalkRate => 0.075..=0.095,
+            BatchStat::StrikeoutRate => 0.190..=0.235,
+            BatchStat::HomeRunRate => 0.025..=0.036,
+            BatchStat::RunsPerGame => 4.0..=4.9,
+            BatchStat::ExtraInnings => 0.07..=0.10,
+        }
+    }
+}
+
+/// League-wide totals over a batch of simulated games.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchTotals {
+    pub games: u32,
+    pub extra_inning_games: u32,
+    pub runs: u32,
+    pub at_bats: u32,
+    pub hits: u32,
+    pub home_runs: u32,
+    pub walks: u32,
+    pub strikeouts: u32,
+}
+
+impl BatchTotals {
+    fn record(&mut self, game: &SimulatedGame) {
+        self.games += 1;
+        // the visitors bat in every inning played, so their line is as long as the game
+        if game.box_score.visiting.line.runs_by_inning.len() > REGULATION_INNINGS {
+            self.extra_inning_games += 1;
+        }
+        for team_box in [&game.box_score.home, &game.box_score.visiting] {
+            self.runs += team_box.line.runs as u32;
+            for line in team_box.batting.iter() {
+                self.at_bats += line.at_bats as u32;
+                self.hits += line.hits as u32;
+                self.home_runs += line.home_runs as u32;
+                self.walks += line.walks as u32;
+                self.strikeouts += line.strikeouts as u32;
+            }
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.games += other.games;
+        self.extra_inning_games += other.extra_inning_games;
+        self.runs += other.runs;
+        self.at_bats += other.at_bats;
+        self.hits += other.hits;
+        self.home_runs += other.home_runs;
+        self.walks += other.walks;
+        self.strikeouts += other.strikeouts;
+    }
+
+    /// Returns how many times batters came up. Every plate appearance here ends in an at bat or a walk, since nobody is hit by a pitch or sacrifices.
+    pub fn plate_appearances(&self) -> u32 {
+        self.at_bats + self.walks
+    }
+
+    /// Returns the given rate over the batch, or 0 if nothing has been played.
+    pub fn value(&self, stat: BatchStat) -> f64 {
+        let ratio = |numerator: u32, denominator: u32| if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 };
+        match stat {
+            BatchStat::BattingAverage => ratio(self.hits, self.at_bats),
+            BatchStat::WalkRate => ratio(self.walks, self.plate_appearances()),
+            BatchStat::StrikeoutRate => ratio(self.strikeouts, self.plate_appearances()),
+            BatchStat::HomeRunRate => ratio(self.home_runs, self.plate_appearances()),
+            BatchStat::RunsPerGame => ratio(self.runs, self.games * 2),
+            BatchStat::ExtraInnings => ratio(self.extra_inning_games, self.games),
+        }
+    }
+}
+
+/// Plays one game of a batch: two different teams drawn at random, each starting a random pitcher from their rotation.
+fn simulate_batch_game(all_players: &HashMap<PlayerId, Player>, teams: &[Team], rng: &mut GameRng) -> SimulatedGame {
+    let mut pairing = teams.choose_multiple(rng, 2).cloned().collect::<Vec<_>>();
+    for team in pairing.iter_mut() {
+        team.current_pitcher = *team.all_pitchers.choose(rng).unwrap();
+    }
+    let visiting_team = pairing.pop().unwrap();
+    let home_team = pairing.pop().unwrap();
+    sim::simulate_game(all_players.clone(), home_team, visiting_team, rng)
+}
+
+/// Plays the given number of games between the given teams, split across the given number of threads, handing each game to the given function to record in that thread's tally. The tallies are then put together with the other function.
+fn run_batch<T: Default + Send>(
+    all_players: &HashMap<PlayerId, Player>,
+    teams: &[Team],
+    games: u32,
+    workers: usize,
+    seed: u64,
+    record: impl Fn(&mut T, &SimulatedGame) + Sync,
+    add: impl Fn(&mut T, &T),
+) -> T {
+    assert!(teams.len() >= 2, "a batch needs at least two teams to play each other");
+    let workers = workers.clamp(1, games.max(1) as usize);
+    let record = &record;
+
+    thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|worker| scope.spawn(move || {
+                let mut tally = T::default();
+                for game in (worker as u32..games).step_by(workers) {
+                    let mut rng = GameRng::seed_from_u64(seed.wrapping_add(game as u64));
+                    record(&mut tally, &simulate_batch_game(all_players, teams, &mut rng));
+                }
+                tally
+            }))
+            .collect::<Vec<_>>();
+
+        let mut tally = T::default();
+        for handle in handles {
+            add(&mut tally, &handle.join().unwrap());
+        }
+        tally
+    })
+}
+
+/// Plays the given number of games between the given teams, which must number at least two, split across the given number of threads. Each game is played from its own seed, counting up from the one given, so the totals come out the same however many threads there are.
+pub fn simulate_batch(all_players: &HashMap<PlayerId, Player>, teams: &[Team], games: u32, workers: usize, seed: u64) -> BatchTotals {
+    run_batch(all_players, teams, games, workers, seed, BatchTotals::record, BatchTotals::add)
+}
+
+/// Plays a batch of games the same way as `simulate_batch`, and returns the run expectancy matrix built from their plays.
+pub fn simulate_run_expectancy(all_players: &HashMap<PlayerId, Player>, teams: &[Team], games: u32, workers: usize, seed: u64) -> RunExpectancy {
+    run_batch(all_players, teams, games, workers, seed, |run_expectancy: &mut RunExpectancy, game| run_expectancy.record_history(&game.history), RunExpectancy::add)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Game;
+    use crate::league::LeagueStructure;
+
+    #[test]
+    fn totals_do_not_depend_on_the_number_of_threads() {
+        let game = Game::with_seed(LeagueStructure::default(), 3);
+        let totals = game.simulate_batch(12, 1, 3);
+        assert_eq!(totals.games, 12);
+        assert_eq!(game.simulate_batch(12, 5, 3), totals);
+    }
+}
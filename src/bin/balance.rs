@@ -0,0 +1,24 @@
+use idbb::Game;
+use idbb::league::LeagueStructure;
+use idbb::text;
+use std::thread;
+use std::time::Instant;
+
+/// Plays a batch of computer-managed games and reports how the league's hitting compares with the major leagues.
+/// Usage: balance [games] [threads] [seed]
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let games = args.next().map_or(1000, |games| games.parse().expect("the number of games should be a whole number"));
+    let workers = args.next().map_or_else(
+        || thread::available_parallelism().map_or(1, |workers| workers.get()),
+        |workers| workers.parse().expect("the number of threads should be a whole number"),
+    );
+    let seed = args.next().map_or_else(rand::random, |seed| seed.parse().expect("the seed should be a whole number"));
+
+    let started = Instant::now();
+    let game = Game::with_seed(LeagueStructure::default(), seed);
+    let totals = game.simulate_batch(games, workers, seed);
+
+    println!("{}", text::describe_batch_totals(&totals));
+    println!("seed {seed}, {workers} threads, {:.1}s", started.elapsed().as_secs_f64());
+}
@@ -0,0 +1,614 @@
+use crate::baseball::{AtBatOutcome, Base, EventsSummary, GameStateSummary, PlayerId, Runner, Team, TeamSide};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+const STARTER_OUTS_FOR_WIN: u8 = 15; // a starter has to go five innings to be credited with the win
+const MAX_SAVE_LEAD: i16 = 3; // the biggest lead a reliever can come in with and still be in a save situation
+const LONG_SAVE_OUTS: u8 = 9; // a reliever who finishes with three innings gets the save whatever the lead
+
+/// One side's line in the box score.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamLine {
+    pub runs_by_inning: Vec<u8>,
+    pub runs: u8,
+    pub hits: u8,
+    pub errors: u8, // the only errors are runners rorzagoth lets through a tag, charged to the fielding side
+}
+
+/// A batter's line in the box score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattingLine {
+    pub player: PlayerId,
+    pub at_bats: u8,
+    pub runs: u8,
+    pub hits: u8,
+    pub doubles: u8,
+    pub triples: u8,
+    pub home_runs: u8,
+    pub runs_batted_in: u8,
+    pub walks: u8,
+    pub strikeouts: u8,
+}
+
+impl BattingLine {
+    fn new(player: PlayerId) -> Self {
+        Self { player, at_bats: 0, runs: 0, hits: 0, doubles: 0, triples: 0, home_runs: 0, runs_batted_in: 0, walks: 0, strikeouts: 0 }
+    }
+}
+
+/// A pitcher's line in the box score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PitchingLine {
+    pub player: PlayerId,
+    pub outs: u8, // innings pitched are kept as outs recorded, to avoid thirds
+    pub hits: u8,
+    pub runs: u8, // charged to them, including runners they left on who scored after they left
+    pub earned_runs: u8,
+    pub walks: u8,
+    pub strikeouts: u8,
+    pub pitches: u16,
+    pub lead_when_entered: i16, // their side's lead when they came in, negative if behind
+    pub lost_lead: bool, // whether the lead was given up while they were pitching
+}
+
+impl PitchingLine {
+    fn new(player: PlayerId, lead_when_entered: i16) -> Self {
+        Self { player, outs: 0, hits: 0, runs: 0, earned_runs: 0, walks: 0, strikeouts: 0, pitches: 0, lead_when_entered, lost_lead: false }
+    }
+
+    /// Returns innings pitched the way box scores write them, with the digit after the point counting outs rather than tenths.
+    pub fn innings_pitched(&self) -> String {
+        format!("{}.{}", self.outs / 3, self.outs % 3)
+    }
+}
+
+/// Everything the box score records for one side: their line score, then their batters in the order they came up and their pitchers in the order they pitched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamBox {
+    pub line: TeamLine,
+    pub batting: Vec<BattingLine>,
+    pub pitching: Vec<PitchingLine>,
+}
+
+impl TeamBox {
+    fn batting_line(&mut self, player: PlayerId) -> &mut BattingLine {
+        let index = match self.batting.iter().position(|line| line.player == player) {
+            Some(index) => index,
+            None => {
+                self.batting.push(BattingLine::new(player));
+                self.batting.len() - 1
+            },
+        };
+        &mut self.batting[index]
+    }
+
+    /// Returns the line of a pitcher who has already appeared.
+    fn pitching_line(&mut self, player: PlayerId) -> &mut PitchingLine {
+        self.pitching.iter_mut().find(|line| line.player == player).unwrap()
+    }
+
+    /// Adds a line for the pitcher if this is their first appearance, noting the lead they came in with.
+    fn enter_pitcher(&mut self, player: PlayerId, lead: i16) {
+        if !self.pitching.iter().any(|line| line.player == player) {
+            self.pitching.push(PitchingLine::new(player, lead));
+        }
+    }
+
+    /// Returns the pitcher currently in the game, or the last one who was.
+    fn current_pitcher(&self) -> PlayerId {
+        self.pitching.last().unwrap().player
+    }
+}
+
+/// The last time either side took the lead, and who the win and loss go to if they keep it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoAhead {
+    pub side: TeamSide,
+    pub pitcher_of_record: PlayerId, // the side's pitcher when they took the lead
+    pub charged_pitcher: PlayerId, // the pitcher charged with the go-ahead run
+}
+
+/// The pitchers credited with the win, loss, save and holds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decisions {
+    pub win: PlayerId,
+    pub loss: PlayerId,
+    pub save: Option<PlayerId>,
+    pub holds: Vec<PlayerId>,
+}
+
+/// Returns how many outs were made between two states. A play can retire more runners than the half-inning has outs left, but only the outs it needed count.
+fn outs_recorded(prev_state: &GameStateSummary, new_state: &GameStateSummary) -> u8 {
+    let same_half_inning = prev_state.half_inning.number == new_state.half_inning.number && prev_state.half_inning.top == new_state.half_inning.top;
+    if same_half_inning {
+        new_state.outs - prev_state.outs
+    } else {
+        3 - prev_state.outs
+    }
+}
+
+/// Returns the lead of the side batting in the given state's half-inning, negative if behind.
+fn batting_lead(state: &GameStateSummary, top: bool) -> i16 {
+    let (batting_runs, fielding_runs) = if top {
+        (state.visiting_team_runs, state.home_team_runs)
+    } else {
+        (state.home_team_runs, state.visiting_team_runs)
+    };
+    batting_runs as i16 - fielding_runs as i16
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxScore {
+    pub home_team: String,
+    pub visiting_team: String,
+    pub home: TeamBox,
+    pub visiting: TeamBox,
+    pub go_ahead: Option<GoAhead>,
+}
+
+impl BoxScore {
+    /// Starts a box score for a game between the given teams, with their starting pitchers already in.
+    pub fn new(home_team: &Team, visiting_team: &Team) -> Self {
+        let mut home = TeamBox::default();
+        home.enter_pitcher(home_team.current_pitcher, 0);
+        let mut visiting = TeamBox::default();
+        visiting.enter_pitcher(visiting_team.current_pitcher, 0);
+
+        Self {
+            home_team: home_team.name.clone(),
+            visiting_team: visiting_team.name.clone(),
+            home,
+            visiting,
+            go_ahead: None,
+        }
+    }
+
+    pub fn line(&self, side: TeamSide) -> &TeamLine {
+        &self.team_box(side).line
+    }
+
+    pub fn team_box(&self, side: TeamSide) -> &TeamBox {
+        match side {
+            TeamSide::Home => &self.home,
+            TeamSide::Visiting => &self.visiting,
+        }
+    }
+
+    /// Returns the batting and fielding sides' boxes for the half-inning the given state is in.
+    fn boxes_mut(&mut self, state: &GameStateSummary) -> (&mut TeamBox, &mut TeamBox) {
+        if state.half_inning.top {
+            (&mut self.visiting, &mut self.home)
+        } else {
+            (&mut self.home, &mut self.visiting)
+        }
+    }
+
+    /// Adds a pitch to the box score, given the state of the game before and after it.
+    ///
+    /// Runs are charged to the pitcher responsible for each runner who scores. A runner who only stayed alive because rorzagoth let them through a tag counts as reaching on an error: their run is unearned and nobody drives them in. No RBI is given on a double play.
+    pub fn record(&mut self, prev_state: &GameStateSummary, new_state: &GameStateSummary, events_summary: &EventsSummary) {
+        let top = prev_state.half_inning.top;
+        let batting_side = if top { TeamSide::Visiting } else { TeamSide::Home };
+        let lead_before = batting_lead(prev_state, top);
+        let runs = (batting_lead(new_state, top) - lead_before) as u8;
+        let outs = outs_recorded(prev_state, new_state);
+        let (batting, fielding) = self.boxes_mut(prev_state);
+        fielding.enter_pitcher(prev_state.pitcher, -lead_before);
+
+        let inning = prev_state.half_inning.number as usize;
+        if batting.line.runs_by_inning.len() < inning {
+            batting.line.runs_by_inning.resize(inning, 0);
+        }
+        batting.line.runs_by_inning[inning - 1] += runs;
+        batting.line.runs += runs;
+
+        let is_hit = matches!(
+            events_summary.at_bat_outcome,
+            Some(AtBatOutcome::Single | AtBatOutcome::Double | AtBatOutcome::Triple | AtBatOutcome::HomeRun),
+        );
+        if is_hit {
+            batting.line.hits += 1;
+        }
+
+        fielding.line.errors += events_summary.runner_advancements.iter()
+            .filter(|advancement| advancement.to_base.is_some() && events_summary.phased_to_safety(advancement.player, batting_side))
+            .count() as u8;
+
+        // lead runners cross the plate first
+        let mut scoring = events_summary.runner_advancements.iter()
+            .filter(|advancement| advancement.to_base == Some(Base::Home))
+            .collect::<Vec<_>>();
+        scoring.sort_by_key(|advancement| std::cmp::Reverse(advancement.from_base));
+
+        let mut runs_batted_in = 0;
+        let mut go_ahead = None;
+        for (i, advancement) in scoring.iter().enumerate() {
+            let runner = prev_state.bases.runner_on(advancement.from_base).unwrap_or(Runner {
+                player: advancement.player,
+                responsible_pitcher: prev_state.pitcher,
+                earned: true,
+            });
+            let on_error = events_summary.phased_to_safety(runner.player, batting_side);
+
+            batting.batting_line(runner.player).runs += 1;
+            let charged = fielding.pitching_line(runner.responsible_pitcher);
+            charged.runs += 1;
+            if runner.earned && !on_error {
+                charged.earned_runs += 1;
+            }
+            if outs < 2 && !on_error {
+                runs_batted_in += 1;
+            }
+
+            let lead = lead_before + i as i16 + 1;
+            if lead == 0 {
+                fielding.pitching_line(prev_state.pitcher).lost_lead = true;
+            } else if lead == 1 {
+                go_ahead = Some(GoAhead {
+                    side: batting_side,
+                    pitcher_of_record: batting.current_pitcher(),
+                    charged_pitcher: runner.responsible_pitcher,
+                });
+            }
+        }
+
+        let batter = batting.batting_line(prev_state.batter);
+        if let Some(at_bat_outcome) = &events_summary.at_bat_outcome {
+            batter.runs_batted_in += runs_batted_in;
+            match at_bat_outcome {
+                AtBatOutcome::Walk => batter.walks += 1,
+                AtBatOutcome::Strikeout => {
+                    batter.at_bats += 1;
+                    batter.strikeouts += 1;
+                },
+                AtBatOutcome::Double => {
+                    batter.at_bats += 1;
+                    batter.doubles += 1;
+                },
+                AtBatOutcome::Triple => {
+                    batter.at_bats += 1;
+                    batter.triples += 1;
+                },
+                AtBatOutcome::HomeRun => {
+                    batter.at_bats += 1;
+                    batter.home_runs += 1;
+                },
+                _ => batter.at_bats += 1,
+            }
+            if is_hit {
+                batter.hits += 1;
+            }
+        }
+
+        let pitcher = fielding.pitching_line(prev_state.pitcher);
+        pitcher.pitches += 1;
+        pitcher.outs += outs;
+        match events_summary.at_bat_outcome {
+            Some(AtBatOutcome::Walk) => pitcher.walks += 1,
+            Some(AtBatOutcome::Strikeout) => pitcher.strikeouts += 1,
+            _ if is_hit => pitcher.hits += 1,
+            _ => {},
+        }
+
+        if go_ahead.is_some() {
+            self.go_ahead = go_ahead;
+        }
+    }
+
+    /// Returns the pitchers credited with the win, loss, save and holds, once the game is over.
+    ///
+    /// The win goes to the winners' pitcher when they took the lead for good, unless that was a starter who didn't last five innings, in which case it goes to the reliever who got the most outs. The loss goes to the pitcher charged with the go-ahead run. Whoever finishes the win gets a save if they came in leading by three or fewer and got at least three outs, or pitched three innings with the lead. Relievers who come in leading by three or fewer, get an out and hand the lead on get a hold.
+    pub fn decisions(&self) -> Option<Decisions> {
+        let winning_side = match self.home.line.runs.cmp(&self.visiting.line.runs) {
+            Ordering::Greater => TeamSide::Home,
+            Ordering::Less => TeamSide::Visiting,
+            Ordering::Equal => return None,
+        };
+        let go_ahead = self.go_ahead.as_ref().filter(|go_ahead| go_ahead.side == winning_side)?;
+        let winners = self.team_box(winning_side);
+
+        let starter = &winners.pitching[0];
+        let mut win = go_ahead.pitcher_of_record;
+        if win == starter.player && starter.outs < STARTER_OUTS_FOR_WIN
+            && let Some(reliever) = winners.pitching[1..].iter().max_by_key(|line| line.outs)
+        {
+            win = reliever.player;
+        }
+
+        let in_save_situation = |line: &PitchingLine| (1..=MAX_SAVE_LEAD).contains(&line.lead_when_entered);
+        let finisher = winners.pitching.last().unwrap();
+        let earns_save = finisher.player != win
+            && finisher.player != starter.player
+            && finisher.lead_when_entered > 0
+            && !finisher.lost_lead
+            && ((in_save_situation(finisher) && finisher.outs >= 3) || finisher.outs >= LONG_SAVE_OUTS);
+        let save = earns_save.then_some(finisher.player);
+
+        let mut holds = Vec::new();
+        for team_box in [&self.visiting, &self.home] {
+            let relievers = &team_box.pitching[1..];
+            for line in relievers.iter().take(relievers.len().saturating_sub(1)) {
+                if in_save_situation(line) && line.outs >= 1 && !line.lost_lead && line.player != win {
+                    holds.push(line.player);
+                }
+            }
+        }
+
+        Some(Decisions { win, loss: go_ahead.charged_pitcher, save, holds })
+    }
+
+    /// Adds an out given up by the batting side without a pitch being thrown. It goes on the pitcher's line, but not as an at-bat for the batter.
+    pub fn record_sacrificed_out(&mut self, prev_state: &GameStateSummary, new_state: &GameStateSummary) {
+        let lead_before = batting_lead(prev_state, prev_state.half_inning.top);
+        let (batting, fielding) = self.boxes_mut(prev_state);
+        batting.batting_line(prev_state.batter);
+        fielding.enter_pitcher(prev_state.pitcher, -lead_before);
+        fielding.pitching_line(prev_state.pitcher).outs += outs_recorded(prev_state, new_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::{Bases, BatterDecision, BattingOutcome, Count, DemonicEvent, DemonicEventKind, GameOutcome, HalfInning, RunnerAdvancement, StrikeZoneLocation};
+    use crate::test_support::{advance, runner, team};
+
+    /// Returns a box score with the home starter `PlayerId(9)` and relievers 10 and 11, facing visitors numbered from 100 with their starter 109.
+    fn box_score() -> BoxScore {
+        BoxScore::new(&team("home", 0), &team("visiting", 100))
+    }
+
+    fn state(number: u8, top: bool, (home_team_runs, visiting_team_runs): (u8, u8), pitcher: u32) -> GameStateSummary {
+        GameStateSummary {
+            home_team_runs,
+            visiting_team_runs,
+            half_inning: HalfInning { number, top },
+            bases: Bases::new_empty(),
+            outs: 0,
+            count: Count { balls: 0, strikes: 0 },
+            batter: PlayerId(if top { 100 } else { 0 }),
+            pitcher: PlayerId(pitcher),
+        }
+    }
+
+    fn events(at_bat_outcome: AtBatOutcome, runner_advancements: Vec<RunnerAdvancement>) -> EventsSummary {
+        EventsSummary {
+            pitch_location: StrikeZoneLocation::In,
+            batter_decision: BatterDecision::Swing,
+            batting_outcome: BattingOutcome::Strike,
+            at_bat_outcome: Some(at_bat_outcome),
+            runner_advancements,
+            demonic_events: Vec::new(),
+            mimed_side: None,
+            game_outcome: GameOutcome::Ongoing,
+        }
+    }
+
+    fn pitching_line(team_box: &TeamBox, player: u32) -> &PitchingLine {
+        team_box.pitching.iter().find(|line| line.player == PlayerId(player)).unwrap()
+    }
+
+    fn batting_line(team_box: &TeamBox, player: u32) -> &BattingLine {
+        team_box.batting.iter().find(|line| line.player == PlayerId(player)).unwrap()
+    }
+
+    /// Records the pitcher striking out the side in one go.
+    fn retire_side(box_score: &mut BoxScore, number: u8, top: bool, score: (u8, u8), pitcher: u32) {
+        let prev_state = state(number, top, score, pitcher);
+        let next = prev_state.half_inning.next();
+        let new_state = state(next.number, next.top, score, pitcher);
+        box_score.record(&prev_state, &new_state, &events(AtBatOutcome::Strikeout, Vec::new()));
+    }
+
+    /// Records a solo home run off the pitcher.
+    fn home_run(box_score: &mut BoxScore, number: u8, top: bool, (home_team_runs, visiting_team_runs): (u8, u8), pitcher: u32) {
+        let prev_state = state(number, top, (home_team_runs, visiting_team_runs), pitcher);
+        let new_score = if top { (home_team_runs, visiting_team_runs + 1) } else { (home_team_runs + 1, visiting_team_runs) };
+        let new_state = state(number, top, new_score, pitcher);
+        let batter = prev_state.batter.0;
+        box_score.record(&prev_state, &new_state, &events(AtBatOutcome::HomeRun, vec![advance(batter, Base::Batting, Some(Base::Home))]));
+    }
+
+    #[test]
+    fn inherited_runner_is_charged_to_the_pitcher_who_let_them_on() {
+        let mut box_score = box_score();
+        let mut prev_state = state(1, true, (0, 0), 10);
+        prev_state.batter = PlayerId(101);
+        prev_state.bases.third = runner(100, 9);
+        let new_state = state(1, true, (0, 1), 10);
+        box_score.record(&prev_state, &new_state, &events(
+            AtBatOutcome::Single,
+            vec![advance(101, Base::Batting, Some(Base::First)), advance(100, Base::Third, Some(Base::Home))],
+        ));
+
+        let starter = pitching_line(&box_score.home, 9);
+        assert_eq!((starter.runs, starter.earned_runs, starter.hits), (1, 1, 0));
+        let reliever = pitching_line(&box_score.home, 10);
+        assert_eq!((reliever.runs, reliever.earned_runs, reliever.hits), (0, 0, 1));
+        assert_eq!(batting_line(&box_score.visiting, 101).runs_batted_in, 1);
+        assert_eq!(batting_line(&box_score.visiting, 100).runs, 1);
+    }
+
+    #[test]
+    fn no_rbi_on_a_double_play() {
+        let mut box_score = box_score();
+        let mut prev_state = state(1, true, (0, 0), 9);
+        prev_state.batter = PlayerId(102);
+        prev_state.bases.first = runner(101, 9);
+        prev_state.bases.third = runner(100, 9);
+        let mut new_state = state(1, true, (0, 1), 9);
+        new_state.outs = 2;
+        box_score.record(&prev_state, &new_state, &events(
+            AtBatOutcome::Out,
+            vec![
+                advance(102, Base::Batting, None),
+                advance(101, Base::First, None),
+                advance(100, Base::Third, Some(Base::Home)),
+            ],
+        ));
+
+        assert_eq!(batting_line(&box_score.visiting, 102).runs_batted_in, 0);
+        let pitcher = pitching_line(&box_score.home, 9);
+        assert_eq!((pitcher.runs, pitcher.earned_runs, pitcher.outs), (1, 1, 2));
+    }
+
+    #[test]
+    fn run_on_a_phased_runner_is_unearned_with_no_rbi() {
+        let mut box_score = box_score();
+        let mut prev_state = state(1, true, (0, 0), 9);
+        prev_state.batter = PlayerId(101);
+        prev_state.bases.second = runner(100, 9);
+        let new_state = state(1, true, (0, 1), 9);
+        let mut events = events(
+            AtBatOutcome::Single,
+            vec![advance(101, Base::Batting, Some(Base::First)), advance(100, Base::Second, Some(Base::Home))],
+        );
+        events.demonic_events.push(DemonicEvent {
+            kind: DemonicEventKind::PhasingRunner,
+            favored_side: TeamSide::Visiting,
+            player: Some(PlayerId(100)),
+        });
+        box_score.record(&prev_state, &new_state, &events);
+
+        let pitcher = pitching_line(&box_score.home, 9);
+        assert_eq!((pitcher.runs, pitcher.earned_runs), (1, 0));
+        assert_eq!(batting_line(&box_score.visiting, 101).runs_batted_in, 0);
+        assert_eq!(box_score.home.line.errors, 1);
+    }
+
+    #[test]
+    fn runner_who_reached_on_an_error_scores_unearned_but_is_driven_in() {
+        let mut box_score = box_score();
+        let mut prev_state = state(1, true, (0, 0), 9);
+        prev_state.batter = PlayerId(101);
+        prev_state.bases.third = Some(Runner { player: PlayerId(100), responsible_pitcher: PlayerId(9), earned: false });
+        let new_state = state(1, true, (0, 1), 9);
+        box_score.record(&prev_state, &new_state, &events(
+            AtBatOutcome::Single,
+            vec![advance(101, Base::Batting, Some(Base::First)), advance(100, Base::Third, Some(Base::Home))],
+        ));
+
+        let pitcher = pitching_line(&box_score.home, 9);
+        assert_eq!((pitcher.runs, pitcher.earned_runs), (1, 0));
+        assert_eq!(batting_line(&box_score.visiting, 101).runs_batted_in, 1);
+    }
+
+    #[test]
+    fn starter_gets_the_win_with_a_hold_and_a_save() {
+        let mut box_score = box_score();
+        for number in 1..=6 {
+            let score = (number.min(2) - 1, 0);
+            retire_side(&mut box_score, number, true, score, 9);
+            if number == 1 {
+                home_run(&mut box_score, 1, false, (0, 0), 109);
+            }
+            retire_side(&mut box_score, number, false, (1, 0), 109);
+        }
+        for number in 7..=8 {
+            retire_side(&mut box_score, number, true, (1, 0), 10);
+            retire_side(&mut box_score, number, false, (1, 0), 109);
+        }
+        retire_side(&mut box_score, 9, true, (1, 0), 11);
+
+        assert_eq!(box_score.decisions(), Some(Decisions {
+            win: PlayerId(9),
+            loss: PlayerId(109),
+            save: Some(PlayerId(11)),
+            holds: vec![PlayerId(10)],
+        }));
+    }
+
+    #[test]
+    fn closer_who_blows_the_lead_gets_the_win_on_a_walk_off() {
+        let mut box_score = box_score();
+        retire_side(&mut box_score, 1, true, (0, 0), 9);
+        home_run(&mut box_score, 1, false, (0, 0), 109);
+        for number in 2..=8 {
+            retire_side(&mut box_score, number, true, (1, 0), 9);
+            retire_side(&mut box_score, number, false, (1, 0), 109);
+        }
+        home_run(&mut box_score, 9, true, (1, 0), 11);
+        retire_side(&mut box_score, 9, true, (1, 1), 11);
+        home_run(&mut box_score, 9, false, (1, 1), 109);
+
+        let decisions = box_score.decisions().unwrap();
+        assert_eq!((decisions.win, decisions.loss, decisions.save), (PlayerId(11), PlayerId(109), None));
+        assert!(pitching_line(&box_score.home, 11).lost_lead);
+    }
+
+    #[test]
+    fn short_start_gives_the_win_to_the_longest_reliever() {
+        let mut box_score = box_score();
+        retire_side(&mut box_score, 1, true, (0, 0), 9);
+        home_run(&mut box_score, 1, false, (0, 0), 109);
+        for number in 1..=9 {
+            if number > 1 {
+                let pitcher = if number <= 7 { 10 } else { 11 };
+                retire_side(&mut box_score, number, true, (1, 0), pitcher);
+            }
+            if number < 9 {
+                retire_side(&mut box_score, number, false, (1, 0), 109);
+            }
+        }
+
+        let decisions = box_score.decisions().unwrap();
+        assert_eq!((decisions.win, decisions.save), (PlayerId(10), Some(PlayerId(11))));
+    }
+
+    #[test]
+    fn lines_count_every_pitch_and_plate_appearance() {
+        let mut box_score = box_score();
+        let mut prev_state = state(1, true, (0, 0), 9);
+        let mut play = |prev_state: &GameStateSummary, new_state: &GameStateSummary, events_summary: EventsSummary| {
+            box_score.record(prev_state, new_state, &events_summary);
+        };
+
+        let mut ball = events(AtBatOutcome::Walk, Vec::new());
+        ball.at_bat_outcome = None;
+        play(&prev_state, &prev_state, ball);
+        play(&prev_state, &prev_state, events(AtBatOutcome::Walk, vec![advance(100, Base::Batting, Some(Base::First))]));
+
+        prev_state.batter = PlayerId(101);
+        prev_state.bases.first = runner(100, 9);
+        play(&prev_state, &prev_state, events(
+            AtBatOutcome::Double,
+            vec![advance(101, Base::Batting, Some(Base::Second)), advance(100, Base::First, Some(Base::Third))],
+        ));
+
+        prev_state.batter = PlayerId(102);
+        prev_state.bases = Bases::new_empty();
+        prev_state.bases.second = runner(101, 9);
+        prev_state.bases.third = runner(100, 9);
+        let mut new_state = prev_state.clone();
+        new_state.outs = 1;
+        play(&prev_state, &new_state, events(AtBatOutcome::Strikeout, Vec::new()));
+
+        prev_state.batter = PlayerId(103);
+        prev_state.outs = 1;
+        new_state.visiting_team_runs = 3;
+        play(&prev_state, &new_state, events(AtBatOutcome::HomeRun, vec![
+            advance(103, Base::Batting, Some(Base::Home)),
+            advance(101, Base::Second, Some(Base::Home)),
+            advance(100, Base::Third, Some(Base::Home)),
+        ]));
+        retire_side(&mut box_score, 1, false, (0, 3), 109);
+        home_run(&mut box_score, 2, true, (0, 3), 9);
+
+        let visiting = &box_score.visiting;
+        assert_eq!((visiting.line.runs_by_inning.as_slice(), visiting.line.runs, visiting.line.hits), ([3, 1].as_slice(), 4, 3));
+        assert_eq!(box_score.home.line.runs_by_inning, [0]);
+        assert_eq!(visiting.batting.iter().map(|line| line.player.0).collect::<Vec<_>>(), [100, 101, 102, 103]);
+
+        let leadoff = batting_line(visiting, 100);
+        assert_eq!((leadoff.at_bats, leadoff.hits, leadoff.walks, leadoff.home_runs, leadoff.runs, leadoff.runs_batted_in), (1, 1, 1, 1, 2, 1));
+        let second = batting_line(visiting, 101);
+        assert_eq!((second.at_bats, second.hits, second.doubles, second.runs), (1, 1, 1, 1));
+        assert_eq!((batting_line(visiting, 102).at_bats, batting_line(visiting, 102).strikeouts), (1, 1));
+        assert_eq!((batting_line(visiting, 103).home_runs, batting_line(visiting, 103).runs_batted_in), (1, 3));
+
+        let starter = pitching_line(&box_score.home, 9);
+        assert_eq!((starter.pitches, starter.hits, starter.walks, starter.strikeouts), (6, 3, 1, 1));
+        assert_eq!((starter.runs, starter.earned_runs, starter.innings_pitched()), (4, 4, "0.1".to_string()));
+        assert_eq!(pitching_line(&box_score.visiting, 109).innings_pitched(), "1.0");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::baseball::{BaseballGame, GameRng, PlayerId, TeamSide};
+use crate::text::Granularity;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const STARTING_GOODWILL: f64 = 0.5;
+pub const PRAYER_GOODWILL: f64 = 0.02;
+pub const BLOOD_SACRIFICE_GOODWILL: f64 = 0.15;
+
+pub const STARTING_FAVORS: u32 = 3;
+pub const FAVORS_PER_SACRIFICED_OUT: u32 = 1;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum DemonPower {
+    IMeMime,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Ritual {
+    Prayer,
+    BloodSacrifice(PlayerId), // the player to be killed
+}
+
+/// Where the expos must be playing for a power to be used.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Venue {
+    Home,
+    Away,
+    Anywhere,
+}
+
+impl Venue {
+    pub fn allows(&self, own_side: TeamSide) -> bool {
+        match self {
+            Self::Home => own_side == TeamSide::Home,
+            Self::Away => own_side == TeamSide::Visiting,
+            Self::Anywhere => true,
+        }
+    }
+}
+
+/// Which half of the inning, from the expos' point of view, a power can be used in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PowerPhase {
+    Offense,
+    Defense,
+}
+
+/// What a demon power did to the game, so that it can be described.
+#[derive(Clone, Debug)]
+pub enum DemonPowerEffect {
+    InvisibleWalls {
+        side: TeamSide,
+        plays: u8,
+    },
+}
+
+/// Everything a power's effect hook needs to know about the request.
+#[derive(Copy, Clone, Debug)]
+pub struct PowerRequest {
+    pub own_side: TeamSide,
+    pub goodwill: f64,
+    pub backfired: bool,
+}
+
+pub struct DemonPowerSpec {
+    pub power: DemonPower,
+    pub favor_cost: u32,
+    pub cooldown: u32, // in pitches
+    pub phases: &'static [PowerPhase],
+    pub granularities: &'static [Granularity],
+    pub venue: Venue,
+    pub success_chance: fn(goodwill: f64) -> f64,
+    pub is_available: fn(game: &BaseballGame) -> bool, // any further conditions the game must meet
+    pub effect: fn(game: &mut BaseballGame, request: PowerRequest) -> DemonPowerEffect,
+}
+
+impl DemonPowerSpec {
+    /// Returns whether the power may be used in the given game right now, ignoring its cost and cooldown.
+    pub fn can_be_used(&self, game: &BaseballGame, own_side: TeamSide, granularity: Granularity) -> bool {
+        let phase = if game.batting_side() == own_side { PowerPhase::Offense } else { PowerPhase::Defense };
+
+        self.venue.allows(own_side)
+            && self.phases.contains(&phase)
+            && self.granularities.contains(&granularity)
+            && (self.is_available)(game)
+    }
+}
+
+pub static DEMON_POWERS: &[DemonPowerSpec] = &[
+    DemonPowerSpec {
+        power: DemonPower::IMeMime,
+        favor_cost: 2,
+        cooldown: 20,
+        phases: &[PowerPhase::Offense, PowerPhase::Defense],
+        granularities: &Granularity::ALL,
+        venue: Venue::Home,
+        success_chance: |goodwill| goodwill,
+        is_available: |game| game.mime().is_none(),
+        effect: i_me_mime,
+    },
+];
+
+pub fn spec(power: DemonPower) -> &'static DemonPowerSpec {
+    DEMON_POWERS.iter().find(|spec| spec.power == power).unwrap()
+}
+
+/// Rolls whether Rorzagoth answers a request in the Expos' favour.
+pub fn power_succeeds(spec: &DemonPowerSpec, goodwill: f64, rng: &mut GameRng) -> bool {
+    rng.random_bool((spec.success_chance)(goodwill).clamp(0.0, 1.0))
+}
+
+/// Returns the chance that rorzagoth acts on their own at any given moment. The demon is quiet when indifferent and restless when they feel strongly about the Expos either way.
+pub fn demonic_event_chance(goodwill: f64) -> f64 {
+    let feeling = (goodwill.clamp(0.0, 1.0) - 0.5).abs() * 2.0;
+    0.01 + 0.04 * feeling
+}
+
+/// Walls in the opposing side, or the expos if it backfires. A pleased demon keeps the opposition walled in for longer, and a displeased one keeps the Expos walled in for longer.
+fn i_me_mime(game: &mut BaseballGame, request: PowerRequest) -> DemonPowerEffect {
+    let strength = if request.backfired { 1.0 - request.goodwill } else { request.goodwill };
+    let plays = 1 + (strength.clamp(0.0, 1.0) * 4.0).round() as u8;
+    let side = if request.backfired { request.own_side } else { request.own_side.other() };
+    game.place_invisible_walls(side, plays);
+
+    DemonPowerEffect::InvisibleWalls { side, plays }
+}
@@ -0,0 +1,181 @@
+use crate::baseball::{GameRng, Level, Player};
+use rand::prelude::*;
+
+pub const PEAK_AGE: u32 = 27;
+const DECLINE_AGE: u32 = 31; // veterans start losing ground after this age
+const ROOKIE_AGE: u32 = 20; // the youngest a generated player can be
+const RETIREMENT_AGE: u32 = 34; // the age players start thinking about retiring
+const MAX_AGE: u32 = 42; // nobody plays past this age
+const RETIREMENT_CHANCE_PER_YEAR: f64 = 0.12; // for each year past `RETIREMENT_AGE - 1`
+const DAILY_DRIFT_CHANCE: f64 = 0.004; // that a player's ratings move on any given day of the season
+const BREAKOUT_CHANCE: f64 = 0.05; // each offseason
+const BUST_CHANCE: f64 = 0.05; // each offseason
+
+/// Returns the highest overall rating a player can develop into, since ratings stay clear of the extremes.
+fn max_overall() -> f64 {
+    Level::High.into()
+}
+
+/// Returns a starting potential for a newly generated player: the younger they are, the more room they may have to grow.
+pub fn random_potential(player: &Player, rng: &mut GameRng) -> f64 {
+    let overall = player.metrics.overall(player.position);
+    let years_to_peak = PEAK_AGE.saturating_sub(player.age) as f64 / (PEAK_AGE - ROOKIE_AGE) as f64;
+    (overall + rng.random_range(0.0..=0.3) * years_to_peak).min(max_overall())
+}
+
+/// Returns whether a player's ratings tend to go up (Some(true)), down (Some(false)) or nowhere in particular (None) at this point in their career.
+fn trend(player: &Player) -> Option<bool> {
+    if player.age > DECLINE_AGE {
+        Some(false)
+    } else if player.age <= PEAK_AGE && player.metrics.overall(player.position) < player.potential {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Moves one of the player's ratings a step in the given direction. Players never improve past their potential.
+fn develop(player: &mut Player, up: bool, rng: &mut GameRng) {
+    if up && player.metrics.overall(player.position) >= player.potential {
+        return;
+    }
+    player.metrics.nudge(player.position, up, rng);
+}
+
+/// Gives the player's ratings a small chance to drift after a day of the season, mostly in the direction their career is heading.
+pub fn drift(player: &mut Player, rng: &mut GameRng) {
+    if !rng.random_bool(DAILY_DRIFT_CHANCE) {
+        return;
+    }
+
+    let up = match trend(player) {
+        Some(up) => rng.random_bool(0.75) == up,
+        None => rng.random_bool(0.5),
+    };
+    develop(player, up, rng);
+}
+
+/// Ages the player a year and develops them over the offseason: young players grow towards their potential, veterans decline, and now and then someone breaks out or busts.
+pub fn develop_over_offseason(player: &mut Player, rng: &mut GameRng) {
+    player.age += 1;
+
+    match trend(player) {
+        Some(true) => {
+            for _ in 0..rng.random_range(1..=2) {
+                develop(player, true, rng);
+            }
+        },
+        Some(false) => {
+            for _ in 0..=(player.age - DECLINE_AGE) / 3 {
+                develop(player, false, rng);
+            }
+        },
+        None => {},
+    }
+
+    if rng.random_bool(BREAKOUT_CHANCE) {
+        player.potential = (player.potential + 0.25).min(max_overall());
+        develop(player, true, rng);
+        develop(player, true, rng);
+    } else if rng.random_bool(BUST_CHANCE) {
+        develop(player, false, rng);
+        develop(player, false, rng);
+        player.potential = player.potential.min(player.metrics.overall(player.position));
+    }
+}
+
+/// Returns whether the player hangs up their spikes this offseason. Older and weaker players are more likely to.
+pub fn retires(player: &Player, rng: &mut GameRng) -> bool {
+    if player.age >= MAX_AGE {
+        return true;
+    }
+    if player.age < RETIREMENT_AGE {
+        return false;
+    }
+
+    let mut chance = RETIREMENT_CHANCE_PER_YEAR * (player.age + 1 - RETIREMENT_AGE) as f64;
+    if player.metrics.overall(player.position) < 0.4 {
+        chance *= 2.0;
+    }
+    rng.random_bool(chance.min(1.0))
+}
+
+/// Returns a random age for a rookie coming into the league.
+pub fn rookie_age(rng: &mut GameRng) -> u32 {
+    rng.random_range(ROOKIE_AGE..=ROOKIE_AGE + 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::{PlayerId, PlayerMetrics, Position};
+
+    /// Returns a player of the given age with random metrics and their potential set to `random_potential`.
+    fn player(age: u32, rng: &mut GameRng) -> Player {
+        let mut player = Player {
+            id: PlayerId(0),
+            name: "Player".to_string(),
+            position: Position::Shortstop,
+            age,
+            potential: 0.0,
+            contract: None,
+            metrics: PlayerMetrics::random(rng),
+        };
+        player.potential = random_potential(&player, rng);
+        player
+    }
+
+    #[test]
+    fn only_young_players_have_room_to_grow() {
+        let mut rng = GameRng::seed_from_u64(0);
+        for _ in 0..200 {
+            let rookie = player(rookie_age(&mut rng), &mut rng);
+            assert!((ROOKIE_AGE..=ROOKIE_AGE + 3).contains(&rookie.age));
+            assert!(rookie.potential >= rookie.metrics.overall(rookie.position).min(max_overall()));
+            assert!(rookie.potential <= max_overall());
+
+            let veteran = player(PEAK_AGE, &mut rng);
+            assert_eq!(veteran.potential, veteran.metrics.overall(veteran.position).min(max_overall()));
+        }
+    }
+
+    #[test]
+    fn young_players_improve_and_veterans_decline() {
+        let mut rng = GameRng::seed_from_u64(0);
+        let mean_change = |age: u32, rng: &mut GameRng| {
+            (0..500).map(|_| {
+                let mut player = player(age, rng);
+                let before = player.metrics.overall(player.position);
+                develop_over_offseason(&mut player, rng);
+                assert_eq!(player.age, age + 1);
+                player.metrics.overall(player.position) - before
+            }).sum::<f64>() / 500.0
+        };
+
+        assert!(mean_change(ROOKIE_AGE, &mut rng) > 0.0);
+        assert!(mean_change(DECLINE_AGE + 5, &mut rng) < 0.0);
+    }
+
+    #[test]
+    fn retirement_gets_likelier_with_age() {
+        let mut rng = GameRng::seed_from_u64(0);
+        let retirement_rate = |age: u32, rng: &mut GameRng| {
+            let mut expected = 0.0;
+            let mut retired = 0;
+            for _ in 0..4000 {
+                let player = player(age, rng);
+                let chance = RETIREMENT_CHANCE_PER_YEAR * (age + 1).saturating_sub(RETIREMENT_AGE) as f64;
+                expected += if player.metrics.overall(player.position) < 0.4 { 2.0 * chance } else { chance }.min(1.0);
+                retired += retires(&player, rng) as u32;
+            }
+            (retired as f64 / 4000.0, expected / 4000.0)
+        };
+
+        assert_eq!(retirement_rate(RETIREMENT_AGE - 1, &mut rng).0, 0.0);
+        assert_eq!(retirement_rate(MAX_AGE, &mut rng).0, 1.0);
+        for age in [RETIREMENT_AGE, RETIREMENT_AGE + 2] {
+            let (rate, expected) = retirement_rate(age, &mut rng);
+            assert!((rate - expected).abs() < 0.03, "{rate} retired at {age}, expected {expected}");
+        }
+    }
+}
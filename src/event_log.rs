@@ -0,0 +1,136 @@
+use crate::backend::{GameOutput, UserInput};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The version of the JSON written here. Bump it whenever the shape of anything serialized changes, so readers can tell old logs from new ones.
+pub const FORMAT_VERSION: u32 = 5;
+
+/// A value along with the format version it was written in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub data: T,
+}
+
+/// Returns the value as JSON, tagged with the current format version.
+pub fn to_json<T: Serialize>(value: &T) -> io::Result<String> {
+    Ok(serde_json::to_string(&Versioned { version: FORMAT_VERSION, data: value })?)
+}
+
+/// Reads a value written by `to_json`. Values written in any other format version are rejected rather than half-read.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> io::Result<T> {
+    let versioned: Versioned<serde_json::Value> = serde_json::from_str(json)?;
+    if versioned.version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("written in format version {}, but only version {FORMAT_VERSION} can be read", versioned.version),
+        ));
+    }
+    Ok(serde_json::from_value(versioned.data)?)
+}
+
+/// One line of a game log: an input the player gave and what came of it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub sequence: u64, // counting from 0 over the whole log
+    pub input: UserInput,
+    pub output: GameOutput,
+}
+
+/// The same as `LogEntry`, but borrowing what it writes.
+#[derive(Serialize)]
+struct LogEntryRef<'a> {
+    sequence: u64,
+    input: &'a UserInput,
+    output: &'a GameOutput,
+}
+
+/// An append-only log of everything that happens in a game, one JSON entry per line.
+#[derive(Debug)]
+pub struct EventLog {
+    file: File,
+    next_sequence: u64,
+}
+
+impl EventLog {
+    /// Opens the log at the given path for appending, creating it if need be. Entries already in the log are kept, whatever format version they were written in, and numbering carries on after them.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let next_sequence = if path.exists() { entry_lines(&fs::read_to_string(path)?).count() as u64 } else { 0 };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file, next_sequence })
+    }
+
+    /// Writes an entry to the end of the log. Each entry goes out in a single write, so a crash can't leave half a line behind another entry.
+    pub fn append(&mut self, input: &UserInput, output: &GameOutput) -> io::Result<()> {
+        let entry = LogEntryRef { sequence: self.next_sequence, input, output };
+        let line = to_json(&entry)? + "\n";
+        self.file.write_all(line.as_bytes())?;
+        self.next_sequence += 1;
+        Ok(())
+    }
+}
+
+fn entry_lines(log: &str) -> impl Iterator<Item = &str> {
+    log.lines().filter(|line| !line.trim().is_empty())
+}
+
+/// Reads every entry in a game log, oldest first. Fails if any entry was written in another format version; use `read_versioned` to get at those.
+pub fn read(path: &Path) -> io::Result<Vec<LogEntry>> {
+    entry_lines(&fs::read_to_string(path)?).map(from_json).collect()
+}
+
+/// Reads every entry in a game log as untyped JSON along with the format version it was written in, oldest first, so logs from older versions can still be looked at or migrated.
+pub fn read_versioned(path: &Path) -> io::Result<Vec<Versioned<serde_json::Value>>> {
+    entry_lines(&fs::read_to_string(path)?).map(|line| Ok(serde_json::from_str(line)?)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::{Bases, Count, GameStateSummary, HalfInning, PlayerId};
+
+    #[test]
+    fn state_summary_json_is_stable() {
+        let state = GameStateSummary {
+            home_team_runs: 2,
+            visiting_team_runs: 1,
+            half_inning: HalfInning { number: 7, top: false },
+            bases: Bases::new_empty(),
+            outs: 1,
+            count: Count { balls: 3, strikes: 2 },
+            batter: PlayerId(12),
+            pitcher: PlayerId(140),
+        };
+        let json = to_json(&state).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":5,"data":{"home_team_runs":2,"visiting_team_runs":1,"half_inning":{"number":7,"top":false},"bases":{"first":null,"second":null,"third":null},"outs":1,"count":{"balls":3,"strikes":2},"batter":12,"pitcher":140}}"#,
+        );
+        assert_eq!(from_json::<GameStateSummary>(&json).unwrap().count, state.count);
+        assert!(from_json::<GameStateSummary>(&json.replace(r#""version":5"#, r#""version":4"#)).is_err());
+    }
+
+    #[test]
+    fn appended_entries_read_back_in_order() {
+        let path = std::env::temp_dir().join(format!("idbb-event-log-{}.jsonl", std::process::id()));
+        fs::write(&path, r#"{"version":4,"data":{"sequence":0}}"#.to_string() + "\n").unwrap();
+
+        let mut event_log = EventLog::open(&path).unwrap();
+        event_log.append(&UserInput::StartNewGame, &GameOutput::StartNewGame).unwrap();
+        let mut event_log = EventLog::open(&path).unwrap();
+        event_log.append(&UserInput::StartNewGame, &GameOutput::StartNewGame).unwrap();
+
+        assert!(read(&path).is_err());
+        let entries = read_versioned(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.version).collect::<Vec<_>>(), [4, FORMAT_VERSION, FORMAT_VERSION]);
+        let current = entries[1..].iter()
+            .map(|entry| serde_json::from_value::<LogEntry>(entry.data.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(current.iter().map(|entry| entry.sequence).collect::<Vec<_>>(), [1, 2]);
+        assert!(matches!((&current[1].input, &current[1].output), (UserInput::StartNewGame, GameOutput::StartNewGame)));
+    }
+}
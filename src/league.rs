@@ -0,0 +1,134 @@
+const TEAMS: &str = include_str!("data/league/teams.csv");
+
+/// A club in the league, as listed in the data files.
+#[derive(Debug, Clone)]
+pub struct Club {
+    pub city: String,
+    pub nickname: String,
+    pub abbreviation: String,
+    pub league: String,
+    pub division: String,
+}
+
+impl Club {
+    pub fn name(&self) -> String {
+        format!("{} {}", self.city, self.nickname)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Division {
+    pub name: String,
+    pub teams: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct League {
+    pub name: String,
+    pub divisions: Vec<Division>,
+}
+
+impl League {
+    pub fn teams(&self) -> impl Iterator<Item = &String> {
+        self.divisions.iter().flat_map(|division| division.teams.iter())
+    }
+}
+
+/// Why league data couldn't be read, with the line it went wrong on, counting from 1.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    WrongFieldCount(usize),
+    EmptyField(usize),
+}
+
+/// Every club and how they are split into leagues and divisions.
+#[derive(Debug, Clone)]
+pub struct LeagueStructure {
+    pub leagues: Vec<League>,
+    pub clubs: Vec<Club>,
+}
+
+impl Default for LeagueStructure {
+    fn default() -> Self {
+        Self::parse(TEAMS).expect("the bundled league data is valid")
+    }
+}
+
+impl LeagueStructure {
+    /// Parses lines of the form `league,division,city,nickname,abbreviation`. Leagues and divisions keep the order they first appear in.
+    pub fn parse(data: &str) -> Result<Self, ParseError> {
+        let clubs = data.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let fields = line.split(',').map(|field| field.trim().to_string()).collect::<Vec<_>>();
+                if fields.iter().any(|field| field.is_empty()) {
+                    return Err(ParseError::EmptyField(i + 1));
+                }
+                let [league, division, city, nickname, abbreviation] = fields.try_into().map_err(|_| ParseError::WrongFieldCount(i + 1))?;
+                Ok(Club { city, nickname, abbreviation, league, division })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut leagues: Vec<League> = Vec::new();
+        for club in clubs.iter() {
+            let league = match leagues.iter().position(|league| league.name == club.league) {
+                Some(i) => &mut leagues[i],
+                None => {
+                    leagues.push(League { name: club.league.clone(), divisions: Vec::new() });
+                    leagues.last_mut().unwrap()
+                },
+            };
+            let division = match league.divisions.iter().position(|division| division.name == club.division) {
+                Some(i) => &mut league.divisions[i],
+                None => {
+                    league.divisions.push(Division { name: club.division.clone(), teams: Vec::new() });
+                    league.divisions.last_mut().unwrap()
+                },
+            };
+            division.teams.push(club.name());
+        }
+
+        Ok(Self { leagues, clubs })
+    }
+
+    pub fn team_names(&self) -> Vec<String> {
+        self.clubs.iter().map(|club| club.name()).collect()
+    }
+
+    pub fn club(&self, team_name: &str) -> Option<&Club> {
+        self.clubs.iter().find(|club| club.name() == team_name)
+    }
+
+    pub fn league_of(&self, team_name: &str) -> &League {
+        let club = self.club(team_name).unwrap();
+        self.leagues.iter().find(|league| league.name == club.league).unwrap()
+    }
+
+    pub fn same_league(&self, a: &str, b: &str) -> bool {
+        self.club(a).unwrap().league == self.club(b).unwrap().league
+    }
+
+    pub fn same_division(&self, a: &str, b: &str) -> bool {
+        let (a, b) = (self.club(a).unwrap(), self.club(b).unwrap());
+        a.league == b.league && a.division == b.division
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clubs_into_leagues_and_divisions() {
+        let league = LeagueStructure::parse("A,East,Montreal,Expos,MON\n\nA,West,San Diego,Padres,SD\nB,East,Boston,Red Sox,BOS\n").unwrap();
+        assert_eq!(league.leagues.len(), 2);
+        assert_eq!(league.leagues[0].divisions.iter().map(|division| division.name.as_str()).collect::<Vec<_>>(), ["East", "West"]);
+        assert!(league.same_league("Montreal Expos", "San Diego Padres"));
+        assert!(!league.same_division("Montreal Expos", "San Diego Padres"));
+
+        assert_eq!(LeagueStructure::parse("A,East,Montreal,Expos,MON\nA,East,San Diego,Padres").unwrap_err(), ParseError::WrongFieldCount(2));
+        assert_eq!(LeagueStructure::parse("\nA,East,,Expos,MON").unwrap_err(), ParseError::EmptyField(2));
+        assert_eq!(LeagueStructure::default().clubs.len(), 30);
+    }
+}
@@ -1,5 +1,21 @@
 mod backend;
+pub mod analytics;
 pub mod baseball;
+pub mod batch;
+pub mod box_score;
+pub mod demon;
+pub mod development;
+pub mod event_log;
+pub mod league;
+pub mod postseason;
+pub mod retrosheet;
+pub mod season;
+pub mod sim;
+pub mod stats;
 pub mod text;
+#[cfg(test)]
+mod test_support;
+pub mod transactions;
+pub mod win_probability;
 
-pub use backend::{Game, UserInput, GameOutput};
+pub use backend::{Game, UserInput, GameOutput, Replay};
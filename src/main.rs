@@ -1,26 +1,48 @@
-use idbb::{Game, UserInput, GameOutput, baseball::StrikeZoneLocation};
+use idbb::demon::Ritual;
+use idbb::{Game, UserInput};
 use prompted::input;
+use std::path::Path;
 
-fn print_options(options: &Vec<UserInput>) {
+fn print_options(game: &Game, options: &[UserInput]) {
     println!("OPTIONS:");
     for (i, option) in options.iter().enumerate() {
-        println!("\t{i}: {option:?}");
+        let player = match option {
+            UserInput::PerformRitual(Ritual::BloodSacrifice(player))
+            | UserInput::AssignFielder(_, player)
+            | UserInput::SetDesignatedHitter(player)
+            | UserInput::ChooseStartingPitcher(player)
+            | UserInput::ChangePitcher(player)
+            | UserInput::SignFreeAgent(player)
+            | UserInput::ReleasePlayer(player)
+            | UserInput::OfferPlayer(player)
+            | UserInput::RequestPlayer(player)
+            | UserInput::WithdrawPlayer(player) => Some(*player),
+            _ => None,
+        };
+        match player {
+            Some(player) => println!("\t{i}: {option:?} ({})", game.player_name(player)),
+            None => println!("\t{i}: {option:?}"),
+        }
     }
 }
 
 fn main() {
     let mut game = Game::new();
+    // pass a path to log every pitch there as json lines
+    if let Some(path) = std::env::args().nth(1) {
+        game.start_event_log(Path::new(&path)).unwrap();
+    }
     let _ = game.process_user_input(&UserInput::StartNewGame);
 
     loop {
         let valid_user_inputs = game.valid_user_inputs();
-        print_options(&valid_user_inputs);
+        print_options(&game, &valid_user_inputs);
         let user_input = input!("Enter your choice: ").parse::<usize>().unwrap();
         let choice = &valid_user_inputs[user_input];
 
         // let description = match game.process_user_input(choice).unwrap() {
         //     GameOutput::PitchOutput { description, .. } => description,
-        //     GameOutput::HalfInningOutput { description, .. } => description,
+        //     GameOutput::PlayOutput { description, .. } => description,
         //     GameOutput::StartNewGame => {
         //         println!("Starting new game...");
         //         continue;
@@ -0,0 +1,277 @@
+use crate::league::LeagueStructure;
+use crate::season::{GameResult, StandingsRow};
+
+#[derive(Debug, Clone)]
+pub struct PostseasonConfig {
+    pub series_lengths: Vec<u32>, // one per round, the last being the world series
+}
+
+impl Default for PostseasonConfig {
+    fn default() -> Self {
+        Self {
+            series_lengths: vec![5, 7, 7],
+        }
+    }
+}
+
+impl PostseasonConfig {
+    /// The number of teams that make the postseason: enough to fill every round. They are split evenly between the leagues.
+    pub fn num_teams(&self) -> usize {
+        1 << self.series_lengths.len()
+    }
+
+    /// Returns the default postseason with its early rounds dropped until every league has enough teams for its share of the bracket.
+    pub fn for_league(league: &LeagueStructure) -> Self {
+        let mut config = Self::default();
+        let smallest_league = league.leagues.iter().map(|league| league.teams().count()).min().unwrap_or(0);
+        while config.series_lengths.len() > 1 && config.num_teams() / league.leagues.len().max(1) > smallest_league {
+            config.series_lengths.remove(0);
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PostseasonGame {
+    pub home_team: String,
+    pub visiting_team: String,
+    pub result: GameResult,
+}
+
+impl PostseasonGame {
+    pub fn winner(&self) -> &str {
+        if self.result.home_team_runs > self.result.visiting_team_runs {
+            &self.home_team
+        } else {
+            &self.visiting_team
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub higher_seed: String,
+    pub lower_seed: String,
+    pub length: u32, // best of this many games
+    pub games: Vec<PostseasonGame>,
+}
+
+impl Series {
+    fn new(higher_seed: String, lower_seed: String, length: u32) -> Self {
+        Self {
+            higher_seed,
+            lower_seed,
+            length,
+            games: Vec::new(),
+        }
+    }
+
+    pub fn involves(&self, team_name: &str) -> bool {
+        self.higher_seed == team_name || self.lower_seed == team_name
+    }
+
+    pub fn wins(&self, team_name: &str) -> u32 {
+        self.games.iter().filter(|game| game.winner() == team_name).count() as u32
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        let wins_needed = self.length / 2 + 1;
+        [&self.higher_seed, &self.lower_seed].into_iter()
+            .find(|team_name| self.wins(team_name) >= wins_needed)
+            .map(|team_name| team_name.as_str())
+    }
+
+    pub fn loser(&self) -> Option<&str> {
+        let winner = self.winner()?;
+        if winner == self.higher_seed { Some(&self.lower_seed) } else { Some(&self.higher_seed) }
+    }
+
+    /// Returns whether the higher seed is at home for the given game (counting from 0). Home field follows the 2-3-2 pattern for seven games, 2-2-1 for five and 1-1-1 for three.
+    fn higher_seed_is_home(&self, game_index: usize) -> bool {
+        match self.length {
+            7 => matches!(game_index, 0 | 1 | 5 | 6),
+            5 => matches!(game_index, 0 | 1 | 4),
+            _ => game_index.is_multiple_of(2),
+        }
+    }
+
+    /// Returns the home and visiting teams of the next game, or None if the series is decided.
+    pub fn next_game(&self) -> Option<(String, String)> {
+        if self.winner().is_some() {
+            return None;
+        }
+
+        if self.higher_seed_is_home(self.games.len()) {
+            Some((self.higher_seed.clone(), self.lower_seed.clone()))
+        } else {
+            Some((self.lower_seed.clone(), self.higher_seed.clone()))
+        }
+    }
+
+    pub fn record_game(&mut self, home_team: String, visiting_team: String, result: GameResult) {
+        self.games.push(PostseasonGame { home_team, visiting_team, result });
+    }
+}
+
+/// Identifies a series in the bracket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesSlot {
+    pub round: usize,
+    pub series: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Postseason {
+    pub config: PostseasonConfig,
+    pub seeds: Vec<Vec<String>>, // one list per league, division winners first
+    pub records: Vec<String>, // every postseason team, best regular season record first
+    pub rounds: Vec<Vec<Series>>, // only rounds that have started
+}
+
+impl Postseason {
+    /// Seeds each league from the final standings, which must be sorted best record first. Division winners are seeded first and the best of the rest fill the remaining places as wild cards.
+    pub fn new(standings: &[StandingsRow], league_structure: &LeagueStructure, config: PostseasonConfig) -> Self {
+        let record_rank = |team_name: &String| standings.iter().position(|row| &row.team == team_name).unwrap();
+        let teams_per_league = (config.num_teams() / league_structure.leagues.len()).max(1);
+
+        let seeds = league_structure.leagues.iter().map(|league| {
+            let mut division_winners = league.divisions.iter()
+                .map(|division| division.teams.iter().min_by_key(|team_name| record_rank(team_name)).unwrap().clone())
+                .collect::<Vec<_>>();
+            division_winners.sort_by_key(record_rank);
+
+            let mut wild_cards = league.teams()
+                .filter(|team_name| !division_winners.contains(team_name))
+                .cloned()
+                .collect::<Vec<_>>();
+            wild_cards.sort_by_key(record_rank);
+
+            let mut league_seeds = division_winners;
+            league_seeds.extend(wild_cards);
+            league_seeds.truncate(teams_per_league);
+            league_seeds
+        }).collect::<Vec<_>>();
+
+        let mut records = seeds.concat();
+        records.sort_by_key(record_rank);
+
+        let mut postseason = Self {
+            config,
+            seeds,
+            records: records.clone(),
+            rounds: Vec::new(),
+        };
+        postseason.start_round(records);
+
+        postseason
+    }
+
+    pub fn made_postseason(&self, team_name: &str) -> bool {
+        self.records.iter().any(|seed| seed == team_name)
+    }
+
+    /// Pairs the best remaining seed with the worst, the second best with the second worst, and so on. Teams only meet teams from their own league until one is left in each.
+    fn start_round(&mut self, teams: Vec<String>) {
+        let by_league = self.seeds.iter()
+            .map(|league_seeds| league_seeds.iter().filter(|team_name| teams.contains(team_name)).cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let brackets = if by_league.iter().all(|league_teams| league_teams.len() >= 2) {
+            by_league
+        } else {
+            vec![self.records.iter().filter(|team_name| teams.contains(team_name)).cloned().collect()]
+        };
+
+        let length = self.config.series_lengths[self.rounds.len()];
+        let series = brackets.iter()
+            .flat_map(|bracket| (0..bracket.len() / 2).map(move |i| Series::new(bracket[i].clone(), bracket[bracket.len() - 1 - i].clone(), length)))
+            .collect();
+        self.rounds.push(series);
+    }
+
+    pub fn current_round(&self) -> usize {
+        self.rounds.len() - 1
+    }
+
+    pub fn series(&self, slot: SeriesSlot) -> &Series {
+        &self.rounds[slot.round][slot.series]
+    }
+
+    pub fn series_mut(&mut self, slot: SeriesSlot) -> &mut Series {
+        &mut self.rounds[slot.round][slot.series]
+    }
+
+    /// Returns every series in the current round that is still being played.
+    pub fn undecided_series(&self) -> Vec<SeriesSlot> {
+        let round = self.current_round();
+        self.rounds[round].iter().enumerate()
+            .filter(|(_, series)| series.winner().is_none())
+            .map(|(series, _)| SeriesSlot { round, series })
+            .collect()
+    }
+
+    /// Returns the series the given team is playing in, if they are still alive and it is undecided.
+    pub fn series_for(&self, team_name: &str) -> Option<SeriesSlot> {
+        self.undecided_series().into_iter().find(|slot| self.series(*slot).involves(team_name))
+    }
+
+    /// Starts the next round once every series in the current one is decided. Returns whether a new round was started.
+    pub fn advance_round(&mut self) -> bool {
+        if !self.undecided_series().is_empty() || self.rounds.len() == self.config.series_lengths.len() {
+            return false;
+        }
+
+        let winners = self.rounds[self.current_round()].iter()
+            .map(|series| series.winner().unwrap().to_string())
+            .collect();
+        self.start_round(winners);
+
+        true
+    }
+
+    pub fn champion(&self) -> Option<&str> {
+        if self.rounds.len() < self.config.series_lengths.len() {
+            return None;
+        }
+        self.rounds.last().unwrap().first().and_then(|series| series.winner())
+    }
+
+    pub fn round_name(&self, round: usize) -> &'static str {
+        match self.config.series_lengths.len() - round {
+            1 => "World Series",
+            2 => "League Championship Series",
+            3 => "Division Series",
+            _ => "Wild Card Series",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays the series out with the seeds trading wins, higher seed first, and returns which seed was at home for each game.
+    fn home_seeds(length: u32) -> Vec<&'static str> {
+        let mut series = Series::new("high".to_string(), "low".to_string(), length);
+        let mut home_seeds = Vec::new();
+        while let Some((home_team, visiting_team)) = series.next_game() {
+            let higher_seed_wins = series.games.len().is_multiple_of(2);
+            let home_team_wins = higher_seed_wins == (home_team == "high");
+            let result = if home_team_wins {
+                GameResult { home_team_runs: 1, visiting_team_runs: 0 }
+            } else {
+                GameResult { home_team_runs: 0, visiting_team_runs: 1 }
+            };
+            home_seeds.push(if home_team == "high" { "high" } else { "low" });
+            series.record_game(home_team, visiting_team, result);
+        }
+        assert_eq!(series.winner(), Some("high"));
+        home_seeds
+    }
+
+    #[test]
+    fn home_field_follows_the_usual_patterns() {
+        assert_eq!(home_seeds(7), ["high", "high", "low", "low", "low", "high", "high"]);
+        assert_eq!(home_seeds(5), ["high", "high", "low", "low", "high"]);
+        assert_eq!(home_seeds(3), ["high", "low", "high"]);
+    }
+}
@@ -0,0 +1,646 @@
+use crate::baseball::{AtBatOutcome, Base, BaseballGame, BatterDecision, BattingOutcome, EventsSummary, FieldLocation, GameEvent, GameOutcome, GameStateSummary, PlayerId, Position, TeamSide};
+use crate::box_score::BoxScore;
+
+const VERSION: u8 = 2;
+
+/// A player's place in a lineup, as written in `start` and `sub` records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Appearance {
+    pub player: String,
+    pub name: String,
+    pub team: u8, // 0 for the visitors, 1 for the home side
+    pub batting_position: u8, // 1 to 9, or 0 for a pitcher who doesn't bat
+    pub fielding_position: u8, // in scorers' numbering, or 10 for the designated hitter
+}
+
+/// A plate appearance, or something that happened partway through one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Play {
+    pub inning: u8,
+    pub team: u8, // the side at bat
+    pub batter: String,
+    pub count: String, // balls then strikes before the last pitch
+    pub pitches: String,
+    pub event: String,
+}
+
+/// One line of a Retrosheet event file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Id(String),
+    Version(u8),
+    Info(String, String),
+    Start(Appearance),
+    Sub(Appearance),
+    Play(Play),
+    Data { kind: String, player: String, value: u32 },
+}
+
+/// Why an event file couldn't be read, with the line it went wrong on, counting from 1.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    UnknownRecord(usize),
+    MalformedRecord(usize),
+}
+
+impl Record {
+    pub fn to_line(&self) -> String {
+        match self {
+            Record::Id(id) => format!("id,{id}"),
+            Record::Version(version) => format!("version,{version}"),
+            Record::Info(key, value) => format!("info,{key},{value}"),
+            Record::Start(appearance) => format!("start,{}", appearance_fields(appearance)),
+            Record::Sub(appearance) => format!("sub,{}", appearance_fields(appearance)),
+            Record::Play(play) => format!("play,{},{},{},{},{},{}", play.inning, play.team, play.batter, play.count, play.pitches, play.event),
+            Record::Data { kind, player, value } => format!("data,{kind},{player},{value}"),
+        }
+    }
+}
+
+const RECORD_KINDS: [&str; 7] = ["id", "version", "info", "start", "sub", "play", "data"];
+
+/// Reads a single line of an event file.
+fn parse_line(line: &str, line_number: usize) -> Result<Record, ParseError> {
+    let fields = split_fields(line);
+    if !RECORD_KINDS.contains(&fields[0].as_str()) {
+        return Err(ParseError::UnknownRecord(line_number));
+    }
+    record_from_fields(&fields).ok_or(ParseError::MalformedRecord(line_number))
+}
+
+fn record_from_fields(fields: &[String]) -> Option<Record> {
+    let number = |i: usize| fields[i].parse::<u8>().ok();
+    let appearance = || Some(Appearance {
+        player: fields[1].clone(),
+        name: fields[2].clone(),
+        team: number(3)?,
+        batting_position: number(4)?,
+        fielding_position: number(5)?,
+    });
+
+    match (fields[0].as_str(), fields.len()) {
+        ("id", 2) => Some(Record::Id(fields[1].clone())),
+        ("version", 2) => Some(Record::Version(number(1)?)),
+        ("info", 3) => Some(Record::Info(fields[1].clone(), fields[2].clone())),
+        ("start", 6) => Some(Record::Start(appearance()?)),
+        ("sub", 6) => Some(Record::Sub(appearance()?)),
+        ("play", 7) => Some(Record::Play(Play {
+            inning: number(1)?,
+            team: number(2)?,
+            batter: fields[3].clone(),
+            count: fields[4].clone(),
+            pitches: fields[5].clone(),
+            event: fields[6].clone(),
+        })),
+        ("data", 4) => Some(Record::Data {
+            kind: fields[1].clone(),
+            player: fields[2].clone(),
+            value: fields[3].parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn appearance_fields(appearance: &Appearance) -> String {
+    format!(
+        "{},\"{}\",{},{},{}",
+        appearance.player, appearance.name, appearance.team, appearance.batting_position, appearance.fielding_position,
+    )
+}
+
+/// Splits a line on commas, leaving commas inside quotes alone and dropping the quotes.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Writes records out as an event file, one per line.
+pub fn write(records: &[Record]) -> String {
+    records.iter().map(|record| record.to_line() + "\n").collect()
+}
+
+/// Reads an event file. Blank lines are skipped.
+pub fn parse(text: &str) -> Result<Vec<Record>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line.trim_end(), i + 1))
+        .collect()
+}
+
+/// What an event file says about a game that the game itself doesn't know.
+#[derive(Debug, Clone)]
+pub struct GameInfo {
+    pub id: String, // by convention the home team's code, the date as digits, then 0, or 1 and 2 for a doubleheader
+    pub visiting_team: String, // three-letter team codes
+    pub home_team: String,
+    pub date: String, // written yyyy/mm/dd
+    pub number: u8, // 0 for a single game, or 1 and 2 for a doubleheader
+}
+
+/// Returns the player's id in event files. Retrosheet ids are eight characters, so ours are the player id padded out.
+pub fn player_id(player: PlayerId) -> String {
+    format!("p{:07}", player.0)
+}
+
+fn team_number(side: TeamSide) -> u8 {
+    match side {
+        TeamSide::Visiting => 0,
+        TeamSide::Home => 1,
+    }
+}
+
+/// Returns the position's number in scorers' notation.
+fn position_number(position: Position) -> u8 {
+    match position {
+        Position::Pitcher => 1,
+        Position::Catcher => 2,
+        Position::FirstBase => 3,
+        Position::SecondBase => 4,
+        Position::ThirdBase => 5,
+        Position::Shortstop => 6,
+        Position::LeftField => 7,
+        Position::CenterField => 8,
+        Position::RightField => 9,
+    }
+}
+
+fn base_code(base: Base) -> &'static str {
+    match base {
+        Base::Batting => "B",
+        Base::First => "1",
+        Base::Second => "2",
+        Base::Third => "3",
+        Base::Home => "H",
+    }
+}
+
+fn next_base(base: Base) -> Base {
+    match base {
+        Base::Batting => Base::First,
+        Base::First => Base::Second,
+        Base::Second => Base::Third,
+        Base::Third | Base::Home => Base::Home,
+    }
+}
+
+fn pitch_code(events_summary: &EventsSummary) -> char {
+    match (&events_summary.batting_outcome, events_summary.batter_decision) {
+        (BattingOutcome::Ball, _) => 'B',
+        (BattingOutcome::Strike, BatterDecision::Take) => 'C',
+        (BattingOutcome::Strike, BatterDecision::Swing) => 'S',
+        (BattingOutcome::Hit { .. }, _) => 'X',
+    }
+}
+
+/// Returns the ball's trajectory code for how far it was hit: ground balls stay close, line drives carry through the infield, and anything further is in the air.
+fn trajectory_code(field_location: FieldLocation) -> &'static str {
+    match field_location {
+        FieldLocation::Close => "G",
+        FieldLocation::Infield => "L",
+        FieldLocation::Outfield | FieldLocation::OutOfPark => "F",
+    }
+}
+
+/// Returns the play code for the plate appearance that ended with the given pitch, including where every runner went.
+///
+/// The simulation doesn't track which fielder made a play, so outs in play go to the fielder who usually makes them at that depth. Runs that come home on a runner rorzagoth let through a tag are marked unearned with no RBI, like runs on an error.
+fn play_code(prev_game_state_summary: &GameStateSummary, events_summary: &EventsSummary) -> String {
+    let batting_side = if prev_game_state_summary.half_inning.top { TeamSide::Visiting } else { TeamSide::Home };
+    let trajectory = match events_summary.batting_outcome {
+        BattingOutcome::Hit { to_field_location } => trajectory_code(to_field_location),
+        _ => "",
+    };
+    let batter_safe = events_summary.runner_advancements.iter()
+        .any(|advancement| advancement.from_base == Base::Batting && advancement.to_base.is_some());
+    let runners_out = events_summary.runner_advancements.iter().filter(|advancement| advancement.to_base.is_none()).count();
+
+    let (mut code, batter_base) = match events_summary.at_bat_outcome.as_ref().unwrap() {
+        AtBatOutcome::Strikeout => ("K".to_string(), None),
+        AtBatOutcome::Walk => ("W".to_string(), Some(Base::First)),
+        AtBatOutcome::Single => (format!("S/{trajectory}"), Some(Base::First)),
+        AtBatOutcome::Double => (format!("D/{trajectory}"), Some(Base::Second)),
+        AtBatOutcome::Triple => (format!("T/{trajectory}"), Some(Base::Third)),
+        AtBatOutcome::HomeRun => ("HR".to_string(), Some(Base::Home)),
+        AtBatOutcome::Out if batter_safe => (format!("FC/{trajectory}"), Some(Base::First)),
+        AtBatOutcome::Out => {
+            let fielders = match trajectory {
+                "G" => "63",
+                "L" => "4",
+                _ => "8",
+            };
+            (format!("{fielders}/{trajectory}"), None)
+        },
+    };
+    let outs = runners_out + matches!(events_summary.at_bat_outcome, Some(AtBatOutcome::Strikeout)) as usize;
+    match outs {
+        2 => code.push_str("/DP"),
+        3 => code.push_str("/TP"),
+        _ => {},
+    }
+
+    let mut runner_advancements = events_summary.runner_advancements.clone();
+    runner_advancements.sort_by_key(|advancement| std::cmp::Reverse(advancement.from_base));
+    let mut advances = Vec::new();
+    for advancement in runner_advancements {
+        if advancement.from_base == Base::Batting && advancement.to_base == batter_base {
+            continue;
+        }
+        match advancement.to_base {
+            Some(to_base) if to_base == advancement.from_base => {},
+            Some(to_base) => {
+                let mut advance = format!("{}-{}", base_code(advancement.from_base), base_code(to_base));
+                if to_base == Base::Home {
+                    let runner = prev_game_state_summary.bases.runner_on(advancement.from_base);
+                    let phased = events_summary.phased_to_safety(advancement.player, batting_side);
+                    if phased || runner.is_some_and(|runner| !runner.earned) {
+                        advance.push_str("(UR)");
+                    }
+                    if phased || outs >= 2 {
+                        advance.push_str("(NR)");
+                    }
+                }
+                advances.push(advance);
+            },
+            None => advances.push(format!("{}X{}", base_code(advancement.from_base), base_code(next_base(advancement.from_base)))),
+        }
+    }
+
+    if !advances.is_empty() {
+        code.push('.');
+        code.push_str(&advances.join(";"));
+    }
+    code
+}
+
+/// Returns the pitcher who started the game for the given side.
+fn starting_pitcher(game: &BaseballGame, side: TeamSide) -> PlayerId {
+    game.history.iter()
+        .find_map(|event| match event {
+            GameEvent::PitchingChange { side: changed_side, replaced, .. } if *changed_side == side => Some(*replaced),
+            _ => None,
+        })
+        .unwrap_or(game.team(side).current_pitcher)
+}
+
+/// Turns a game's history into the records of a Retrosheet event file: the game's id and info, the starting lineups, a play for every plate appearance and pitching change, and each pitcher's earned runs.
+///
+/// Outs offered up to rorzagoth don't have a code of their own, so they're scored as a sacrifice to the catcher, which also keeps them from counting as an at-bat.
+pub fn export(game: &BaseballGame, info: &GameInfo) -> Vec<Record> {
+    let mut home_team = game.home_team.clone();
+    home_team.current_pitcher = starting_pitcher(game, TeamSide::Home);
+    let mut visiting_team = game.visiting_team.clone();
+    visiting_team.current_pitcher = starting_pitcher(game, TeamSide::Visiting);
+
+    let mut box_score = BoxScore::new(&home_team, &visiting_team);
+    for event in game.history.iter() {
+        match event {
+            GameEvent::Pitch { prev_game_state_summary, events_summary, new_game_state_summary } => {
+                box_score.record(prev_game_state_summary, new_game_state_summary, events_summary);
+            },
+            GameEvent::SacrificedOut { prev_game_state_summary, new_game_state_summary } => {
+                box_score.record_sacrificed_out(prev_game_state_summary, new_game_state_summary);
+            },
+            GameEvent::PitchingChange { .. } => {},
+        }
+    }
+
+    let mut records = vec![
+        Record::Id(info.id.clone()),
+        Record::Version(VERSION),
+        Record::Info("visteam".to_string(), info.visiting_team.clone()),
+        Record::Info("hometeam".to_string(), info.home_team.clone()),
+        Record::Info("date".to_string(), info.date.clone()),
+        Record::Info("number".to_string(), info.number.to_string()),
+        Record::Info("usedh".to_string(), "true".to_string()),
+    ];
+    if game.state.game_outcome != GameOutcome::Ongoing
+        && let Some(decisions) = box_score.decisions()
+    {
+        records.push(Record::Info("wp".to_string(), player_id(decisions.win)));
+        records.push(Record::Info("lp".to_string(), player_id(decisions.loss)));
+        records.push(Record::Info("save".to_string(), decisions.save.map(player_id).unwrap_or_default()));
+    }
+
+    let appearance = |player: PlayerId, side: TeamSide, batting_position: u8, fielding_position: u8| Appearance {
+        player: player_id(player),
+        name: game.player(player).name.clone(),
+        team: team_number(side),
+        batting_position,
+        fielding_position,
+    };
+    for (side, team) in [(TeamSide::Visiting, &visiting_team), (TeamSide::Home, &home_team)] {
+        for (i, batter) in team.batting_order.iter().enumerate() {
+            let fielding_position = match team.fielders.iter().position(|fielder| fielder == batter) {
+                Some(index) => position_number(Position::FIELDING[index]),
+                None => 10,
+            };
+            records.push(Record::Start(appearance(*batter, side, i as u8 + 1, fielding_position)));
+        }
+        records.push(Record::Start(appearance(team.current_pitcher, side, 0, position_number(Position::Pitcher))));
+    }
+
+    let mut pitches = String::new(); // thrown so far in the current plate appearance
+    let mut current_state = None;
+    for event in game.history.iter() {
+        match event {
+            GameEvent::Pitch { prev_game_state_summary: prev, events_summary, new_game_state_summary } => {
+                pitches.push(pitch_code(events_summary));
+                if events_summary.at_bat_outcome.is_some() {
+                    records.push(Record::Play(Play {
+                        inning: prev.half_inning.number,
+                        team: team_number(if prev.half_inning.top { TeamSide::Visiting } else { TeamSide::Home }),
+                        batter: player_id(prev.batter),
+                        count: format!("{}{}", prev.count.balls, prev.count.strikes),
+                        pitches: std::mem::take(&mut pitches),
+                        event: play_code(prev, events_summary),
+                    }));
+                }
+                current_state = Some(new_game_state_summary);
+            },
+            GameEvent::SacrificedOut { prev_game_state_summary: prev, new_game_state_summary } => {
+                records.push(Record::Play(Play {
+                    inning: prev.half_inning.number,
+                    team: team_number(if prev.half_inning.top { TeamSide::Visiting } else { TeamSide::Home }),
+                    batter: player_id(prev.batter),
+                    count: format!("{}{}", prev.count.balls, prev.count.strikes),
+                    pitches: std::mem::take(&mut pitches),
+                    event: "2/SH".to_string(),
+                }));
+                current_state = Some(new_game_state_summary);
+            },
+            GameEvent::PitchingChange { side, pitcher, .. } => {
+                let (inning, batting_side, batter, count) = match current_state {
+                    Some(state) => (
+                        state.half_inning.number,
+                        if state.half_inning.top { TeamSide::Visiting } else { TeamSide::Home },
+                        state.batter,
+                        format!("{}{}", state.count.balls, state.count.strikes),
+                    ),
+                    None => (1, TeamSide::Visiting, visiting_team.batting_order[0], "00".to_string()),
+                };
+                records.push(Record::Play(Play {
+                    inning,
+                    team: team_number(batting_side),
+                    batter: player_id(batter),
+                    count,
+                    pitches: pitches.clone(),
+                    event: "NP".to_string(),
+                }));
+                records.push(Record::Sub(appearance(*pitcher, *side, 0, position_number(Position::Pitcher))));
+            },
+        }
+    }
+
+    for side in [TeamSide::Visiting, TeamSide::Home] {
+        for line in box_score.team_box(side).pitching.iter() {
+            records.push(Record::Data {
+                kind: "er".to_string(),
+                player: player_id(line.player),
+                value: line.earned_runs as u32,
+            });
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::{Count, GameRng, Runner, RunnerAdvancement, StrikeZoneLocation};
+    use crate::test_support::{advance, players, team};
+    use rand::SeedableRng;
+
+    const SAMPLE: &str = "\
+id,MON000100010
+version,2
+info,visteam,SD
+info,hometeam,MON
+info,date,0001/04/01
+info,number,0
+info,usedh,true
+info,wp,p0000009
+info,lp,p0000110
+info,save,
+start,p0000100,\"Player 100\",0,1,10
+start,p0000101,\"Player 101\",0,2,2
+start,p0000102,\"Player 102\",0,3,3
+start,p0000103,\"Player 103\",0,4,4
+start,p0000104,\"Player 104\",0,5,5
+start,p0000105,\"Player 105\",0,6,6
+start,p0000106,\"Player 106\",0,7,7
+start,p0000107,\"Player 107\",0,8,8
+start,p0000108,\"Player 108\",0,9,9
+start,p0000109,\"Player 109\",0,0,1
+start,p0000000,\"Player 0\",1,1,10
+start,p0000001,\"Player 1\",1,2,2
+start,p0000002,\"Player 2\",1,3,3
+start,p0000003,\"Player 3\",1,4,4
+start,p0000004,\"Player 4\",1,5,5
+start,p0000005,\"Player 5\",1,6,6
+start,p0000006,\"Player 6\",1,7,7
+start,p0000007,\"Player 7\",1,8,8
+start,p0000008,\"Player 8\",1,9,9
+start,p0000009,\"Player 9\",1,0,1
+play,1,0,p0000100,11,BCX,S/G
+play,1,0,p0000101,30,BBBB,W.1-2
+play,1,0,p0000102,01,SX,63/G/DP.2-3;1X2
+play,1,0,p0000103,02,CCS,K
+play,1,1,p0000000,00,,NP
+sub,p0000110,\"Player 110\",0,0,1
+play,1,1,p0000000,00,X,HR
+play,1,1,p0000001,01,C,2/SH
+play,1,1,p0000002,00,X,8/F
+play,1,1,p0000003,10,BX,4/L
+data,er,p0000109,0
+data,er,p0000110,1
+data,er,p0000009,0
+";
+
+    /// Writes a game's history by hand, keeping the game state in step the way the simulation would.
+    struct Scorer {
+        game: BaseballGame,
+        state: GameStateSummary,
+        batter_indexes: [usize; 2],
+    }
+
+    impl Scorer {
+        fn new() -> Self {
+            let game = BaseballGame::new(players(), team("Montreal Expos", 0), team("San Diego Padres", 100), GameRng::seed_from_u64(0));
+            let state = game.state_summary();
+            Self { game, state, batter_indexes: [0, 0] }
+        }
+
+        fn batting_side(&self) -> TeamSide {
+            if self.state.half_inning.top { TeamSide::Visiting } else { TeamSide::Home }
+        }
+
+        /// Moves on to the next batter, and to the next half-inning once there are three outs.
+        fn end_plate_appearance(&mut self, state: &mut GameStateSummary) {
+            state.count = Count { balls: 0, strikes: 0 };
+            let side = self.batting_side();
+            let index = &mut self.batter_indexes[team_number(side) as usize];
+            *index = (*index + 1) % 9;
+            if state.outs >= 3 {
+                state.half_inning = state.half_inning.next();
+                state.outs = 0;
+                state.bases.first = None;
+                state.bases.second = None;
+                state.bases.third = None;
+            }
+            let side = if state.half_inning.top { TeamSide::Visiting } else { TeamSide::Home };
+            state.batter = self.game.team(side).batting_order[self.batter_indexes[team_number(side) as usize]];
+            state.pitcher = self.game.team(side.other()).current_pitcher;
+        }
+
+        fn pitch(&mut self, batter_decision: BatterDecision, batting_outcome: BattingOutcome, at_bat_outcome: Option<AtBatOutcome>, runner_advancements: Vec<RunnerAdvancement>) {
+            self.state.pitcher = self.game.team(self.batting_side().other()).current_pitcher;
+            let prev_game_state_summary = self.state.clone();
+            let mut state = self.state.clone();
+            match batting_outcome {
+                BattingOutcome::Ball => state.count.balls += 1,
+                BattingOutcome::Strike => state.count.strikes += 1,
+                BattingOutcome::Hit { .. } => {},
+            }
+            if matches!(at_bat_outcome, Some(AtBatOutcome::Strikeout)) {
+                state.outs += 1;
+            }
+
+            let mut sorted = runner_advancements.clone();
+            sorted.sort_by_key(|advancement| std::cmp::Reverse(advancement.from_base));
+            for advancement in sorted {
+                let runner = match advancement.from_base {
+                    Base::First => state.bases.first.take(),
+                    Base::Second => state.bases.second.take(),
+                    Base::Third => state.bases.third.take(),
+                    _ => Some(Runner { player: advancement.player, responsible_pitcher: state.pitcher, earned: true }),
+                };
+                match advancement.to_base {
+                    Some(Base::First) => state.bases.first = runner,
+                    Some(Base::Second) => state.bases.second = runner,
+                    Some(Base::Third) => state.bases.third = runner,
+                    Some(Base::Home) if state.half_inning.top => state.visiting_team_runs += 1,
+                    Some(_) => state.home_team_runs += 1,
+                    None => state.outs += 1,
+                }
+            }
+            if at_bat_outcome.is_some() {
+                self.end_plate_appearance(&mut state);
+            }
+
+            self.game.history.push(GameEvent::Pitch {
+                prev_game_state_summary,
+                events_summary: EventsSummary {
+                    pitch_location: if matches!(batting_outcome, BattingOutcome::Ball) { StrikeZoneLocation::Out } else { StrikeZoneLocation::In },
+                    batter_decision,
+                    batting_outcome,
+                    at_bat_outcome,
+                    runner_advancements,
+                    demonic_events: Vec::new(),
+                    mimed_side: None,
+                    game_outcome: GameOutcome::Ongoing,
+                },
+                new_game_state_summary: state.clone(),
+            });
+            self.state = state;
+        }
+
+        fn ball(&mut self) {
+            self.pitch(BatterDecision::Take, BattingOutcome::Ball, None, Vec::new());
+        }
+
+        fn called_strike(&mut self) {
+            self.pitch(BatterDecision::Take, BattingOutcome::Strike, None, Vec::new());
+        }
+
+        fn swinging_strike(&mut self) {
+            self.pitch(BatterDecision::Swing, BattingOutcome::Strike, None, Vec::new());
+        }
+
+        fn in_play(&mut self, to_field_location: FieldLocation, at_bat_outcome: AtBatOutcome, runner_advancements: Vec<RunnerAdvancement>) {
+            self.pitch(BatterDecision::Swing, BattingOutcome::Hit { to_field_location }, Some(at_bat_outcome), runner_advancements);
+        }
+
+        fn sacrifice_out(&mut self) {
+            let prev_game_state_summary = self.state.clone();
+            let mut state = self.state.clone();
+            state.outs += 1;
+            self.end_plate_appearance(&mut state);
+            self.game.history.push(GameEvent::SacrificedOut { prev_game_state_summary, new_game_state_summary: state.clone() });
+            self.state = state;
+        }
+    }
+
+    /// Plays out the sample game: one inning, with a double play, a pitching change, a home run and an out offered to rorzagoth.
+    fn sample_game() -> BaseballGame {
+        let mut scorer = Scorer::new();
+
+        scorer.ball();
+        scorer.called_strike();
+        scorer.in_play(FieldLocation::Close, AtBatOutcome::Single, vec![advance(100, Base::Batting, Some(Base::First))]);
+        for _ in 0..3 {
+            scorer.ball();
+        }
+        scorer.pitch(
+            BatterDecision::Take,
+            BattingOutcome::Ball,
+            Some(AtBatOutcome::Walk),
+            vec![advance(101, Base::Batting, Some(Base::First)), advance(100, Base::First, Some(Base::Second))],
+        );
+        scorer.swinging_strike();
+        scorer.in_play(FieldLocation::Close, AtBatOutcome::Out, vec![
+            advance(102, Base::Batting, None),
+            advance(101, Base::First, None),
+            advance(100, Base::Second, Some(Base::Third)),
+        ]);
+        scorer.called_strike();
+        scorer.called_strike();
+        scorer.pitch(BatterDecision::Swing, BattingOutcome::Strike, Some(AtBatOutcome::Strikeout), Vec::new());
+
+        scorer.game.change_pitcher(TeamSide::Visiting, PlayerId(110));
+        scorer.in_play(FieldLocation::OutOfPark, AtBatOutcome::HomeRun, vec![advance(0, Base::Batting, Some(Base::Home))]);
+        scorer.called_strike();
+        scorer.sacrifice_out();
+        scorer.in_play(FieldLocation::Outfield, AtBatOutcome::Out, vec![advance(2, Base::Batting, None)]);
+        scorer.ball();
+        scorer.in_play(FieldLocation::Infield, AtBatOutcome::Out, vec![advance(3, Base::Batting, None)]);
+
+        scorer.game.state.game_outcome = GameOutcome::HomeTeamWins;
+        scorer.game
+    }
+
+    #[test]
+    fn sample_file_round_trips() {
+        let records = parse(SAMPLE).unwrap();
+        assert_eq!(write(&records), SAMPLE);
+    }
+
+    #[test]
+    fn export_matches_sample_file() {
+        let info = GameInfo {
+            id: "MON000100010".to_string(),
+            visiting_team: "SD".to_string(),
+            home_team: "MON".to_string(),
+            date: "0001/04/01".to_string(),
+            number: 0,
+        };
+        let records = export(&sample_game(), &info);
+        assert_eq!(records, parse(SAMPLE).unwrap());
+        assert_eq!(parse(&write(&records)).unwrap(), records);
+    }
+
+    #[test]
+    fn parse_reports_the_bad_line() {
+        assert_eq!(parse("id,MON000100010\ncom,\"a comment\""), Err(ParseError::UnknownRecord(2)));
+        assert_eq!(parse("version,2\nplay,1,0,p0000100,11"), Err(ParseError::MalformedRecord(2)));
+    }
+}
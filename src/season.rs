@@ -0,0 +1,418 @@
+use crate::baseball::GameRng;
+use crate::league::LeagueStructure;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct SeasonConfig {
+    pub games_per_team: u32,
+    pub series_length: u32, // the most games in a series; games between two teams are split into series of at most this many
+    pub series_between_off_days: u32, // an off day follows every this many rounds of series
+    pub games_against_division_rival: u32,
+    pub games_against_league_rival: u32, // same league, different division
+    pub games_against_other_league: u32,
+}
+
+impl Default for SeasonConfig {
+    fn default() -> Self {
+        Self {
+            games_per_team: 162,
+            series_length: 3,
+            series_between_off_days: 2,
+            games_against_division_rival: 13,
+            games_against_league_rival: 5,
+            games_against_other_league: 4,
+        }
+    }
+}
+
+impl SeasonConfig {
+    /// Returns how many games the two teams play against each other.
+    pub fn games_between(&self, league: &LeagueStructure, a: &str, b: &str) -> u32 {
+        if league.same_division(a, b) {
+            self.games_against_division_rival
+        } else if league.same_league(a, b) {
+            self.games_against_league_rival
+        } else {
+            self.games_against_other_league
+        }
+    }
+
+    /// Returns a config that fits the given league: the default games against each kind of opponent if they come to the same number of games for every team, or the same games against every opponent if they don't.
+    pub fn for_league(league: &LeagueStructure) -> Self {
+        let mut config = Self::default();
+        let team_names = league.team_names();
+        let Some(first_team) = team_names.first() else {
+            return config;
+        };
+        config.games_per_team = team_names.iter().filter(|b| *b != first_team).map(|b| config.games_between(league, first_team, b)).sum();
+        if !config.fits(league) {
+            config.games_against_division_rival = config.games_against_league_rival;
+            config.games_against_other_league = config.games_against_league_rival;
+            config.games_per_team = config.games_against_league_rival * (team_names.len() as u32 - 1);
+        }
+        config
+    }
+
+    /// Returns whether the games against each opponent add up to `games_per_team` for every team in the league.
+    pub fn fits(&self, league: &LeagueStructure) -> bool {
+        let team_names = league.team_names();
+        team_names.iter().all(|a| {
+            let games = team_names.iter().filter(|b| *b != a).map(|b| self.games_between(league, a, b)).sum::<u32>();
+            games == self.games_per_team
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub home_team_runs: u8,
+    pub visiting_team_runs: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledGame {
+    pub home_team: String,
+    pub visiting_team: String,
+    pub result: Option<GameResult>, // None until the game has been played
+}
+
+impl ScheduledGame {
+    pub fn involves(&self, team_name: &str) -> bool {
+        self.home_team == team_name || self.visiting_team == team_name
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduleDay {
+    pub games: Vec<ScheduledGame>, // empty on off days
+}
+
+/// Identifies a game on the schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSlot {
+    pub day: usize,
+    pub game: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub days: Vec<ScheduleDay>,
+}
+
+impl Schedule {
+    /// Generates a schedule of series, with more games against closer rivals as set in the config, which must fit the league. Each pair of teams splits their games at home as evenly as they can, and when that leaves one of them a game up, the extra home games are shared out so every team hosts half its games, or as near as the league allows. Each round, every team plays at most one series, all of the same length, and the teams with the most series left are paired first.
+    pub fn generate(league: &LeagueStructure, config: &SeasonConfig, rng: &mut GameRng) -> Self {
+        assert!(config.fits(league), "the games against each opponent must add up to {} for every team", config.games_per_team);
+        let team_names = league.team_names();
+
+        let mut pairs = team_names.iter().enumerate()
+            .flat_map(|(i, a)| team_names[i + 1..].iter().map(move |b| (a.clone(), b.clone())))
+            .collect::<Vec<_>>();
+        pairs.shuffle(rng);
+
+        let odd_pairs = pairs.iter()
+            .filter(|(a, b)| config.games_between(league, a, b) % 2 == 1)
+            .cloned()
+            .collect::<Vec<_>>();
+        let extra_game_hosts = share_out(&odd_pairs);
+
+        // every series to be played: home team, visiting team and number of games
+        let mut series = Vec::new();
+        for (a, b) in pairs.iter() {
+            let games = config.games_between(league, a, b);
+            let a_hosts_extra_game = extra_game_hosts.contains(&(a.clone(), b.clone()));
+            let a_home_games = games / 2 + (games % 2 == 1 && a_hosts_extra_game) as u32;
+            for (home_team, visiting_team, home_games) in [(a, b, a_home_games), (b, a, games - a_home_games)] {
+                for length in split_into_series(home_games, config.series_length) {
+                    series.push((home_team.clone(), visiting_team.clone(), length));
+                }
+            }
+        }
+        series.shuffle(rng);
+
+        let mut days = Vec::new();
+        let mut series_since_off_day = 0;
+
+        while !series.is_empty() {
+            let mut series_left = HashMap::new();
+            for (home_team, visiting_team, _) in series.iter() {
+                *series_left.entry(home_team.clone()).or_insert(0) += 1;
+                *series_left.entry(visiting_team.clone()).or_insert(0) += 1;
+            }
+            series.sort_by_key(|(home_team, visiting_team, _)| Reverse(series_left[home_team] + series_left[visiting_team]));
+
+            // the round's series all run as long as the most pressing one, so nobody sits idle while others finish
+            let round_length = series[0].2;
+            let mut busy = HashSet::new();
+            let mut pairings = Vec::new();
+            series.retain(|(home_team, visiting_team, length)| {
+                if *length != round_length || busy.contains(home_team) || busy.contains(visiting_team) {
+                    return true;
+                }
+                busy.insert(home_team.clone());
+                busy.insert(visiting_team.clone());
+                pairings.push((home_team.clone(), visiting_team.clone()));
+                false
+            });
+
+            for _ in 0..round_length {
+                let games = pairings.iter()
+                    .map(|(home_team, visiting_team)| ScheduledGame {
+                        home_team: home_team.clone(),
+                        visiting_team: visiting_team.clone(),
+                        result: None,
+                    })
+                    .collect::<Vec<_>>();
+                days.push(ScheduleDay { games });
+            }
+
+            series_since_off_day += 1;
+            if series_since_off_day == config.series_between_off_days {
+                days.push(ScheduleDay { games: Vec::new() });
+                series_since_off_day = 0;
+            }
+        }
+
+        // don't end the season on off days
+        while days.last().is_some_and(|day| day.games.is_empty()) {
+            days.pop();
+        }
+
+        Self { days }
+    }
+
+    pub fn game(&self, slot: GameSlot) -> &ScheduledGame {
+        &self.days[slot.day].games[slot.game]
+    }
+
+    /// Returns the given team's first game on or after the given day.
+    pub fn next_game_for(&self, team_name: &str, from_day: usize) -> Option<GameSlot> {
+        self.days.iter().enumerate().skip(from_day).find_map(|(day, schedule_day)| {
+            schedule_day.games.iter()
+                .position(|game| game.involves(team_name) && game.result.is_none())
+                .map(|game| GameSlot { day, game })
+        })
+    }
+
+    /// Returns every played game, in the order they were played.
+    pub fn played_games(&self) -> impl Iterator<Item = (&ScheduledGame, GameResult)> {
+        self.days.iter()
+            .flat_map(|day| day.games.iter())
+            .filter_map(|game| game.result.map(|result| (game, result)))
+    }
+}
+
+/// Splits the given number of games into as few series as the series length allows, as close to the same length as they can be.
+fn split_into_series(games: u32, series_length: u32) -> Vec<u32> {
+    let series = games.div_ceil(series_length.max(1));
+    (0..series).map(|i| games / series + (i < games % series) as u32).collect()
+}
+
+/// Picks which team of each pair hosts the extra game, so that every team hosts as many extra games as it visits for. Following trails of unused pairs from team to team and handing the extra game to the team each step leaves does it: a trail leaves a team once for every time it arrives, unless the team has an odd number of pairs, and then it's off by one.
+fn share_out(pairs: &[(String, String)]) -> HashSet<(String, String)> {
+    let mut unused = pairs.iter().map(|(a, b)| (a, b)).collect::<Vec<_>>();
+    let mut hosts = HashSet::new();
+    while let Some((a, b)) = unused.pop() {
+        hosts.insert((a.clone(), b.clone()));
+        let mut current = b;
+        while let Some(i) = unused.iter().position(|(x, y)| *x == current || *y == current) {
+            let (x, y) = unused.swap_remove(i);
+            let next = if x == current { y } else { x };
+            hosts.insert((current.clone(), next.clone()));
+            current = next;
+        }
+    }
+    hosts
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Streak {
+    Won(u32),
+    Lost(u32),
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingsRow {
+    pub team: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub games_behind: f64,
+    pub run_differential: i32,
+    pub streak: Streak,
+}
+
+impl StandingsRow {
+    pub fn win_percentage(&self) -> f64 {
+        let games = self.wins + self.losses;
+        if games == 0 {
+            0.0
+        } else {
+            self.wins as f64 / games as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivisionStandings {
+    pub league: String,
+    pub division: String,
+    pub rows: Vec<StandingsRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Season {
+    pub config: SeasonConfig,
+    pub schedule: Schedule,
+    pub current_day: usize,
+}
+
+impl Season {
+    pub fn new(league: &LeagueStructure, config: SeasonConfig, rng: &mut GameRng) -> Self {
+        let schedule = Schedule::generate(league, &config, rng);
+
+        Self {
+            config,
+            schedule,
+            current_day: 0,
+        }
+    }
+
+    pub fn record_result(&mut self, slot: GameSlot, result: GameResult) {
+        self.schedule.days[slot.day].games[slot.game].result = Some(result);
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.schedule.days.iter().all(|day| day.games.iter().all(|game| game.result.is_some()))
+    }
+
+    /// Returns the standings of the given teams, best record first, with games behind counted from the best of them.
+    pub fn standings(&self, team_names: &[String]) -> Vec<StandingsRow> {
+        let mut rows = team_names.iter().map(|team_name| {
+            let mut row = StandingsRow {
+                team: team_name.clone(),
+                wins: 0,
+                losses: 0,
+                games_behind: 0.0,
+                run_differential: 0,
+                streak: Streak::None,
+            };
+
+            for (game, result) in self.schedule.played_games().filter(|(game, _)| game.involves(team_name)) {
+                let (runs_for, runs_against) = if &game.home_team == team_name {
+                    (result.home_team_runs, result.visiting_team_runs)
+                } else {
+                    (result.visiting_team_runs, result.home_team_runs)
+                };
+                row.run_differential += runs_for as i32 - runs_against as i32;
+
+                if runs_for > runs_against {
+                    row.wins += 1;
+                    row.streak = match row.streak {
+                        Streak::Won(n) => Streak::Won(n + 1),
+                        _ => Streak::Won(1),
+                    };
+                } else {
+                    row.losses += 1;
+                    row.streak = match row.streak {
+                        Streak::Lost(n) => Streak::Lost(n + 1),
+                        _ => Streak::Lost(1),
+                    };
+                }
+            }
+
+            row
+        }).collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| b.win_percentage().total_cmp(&a.win_percentage()).then(b.wins.cmp(&a.wins)));
+        if let Some(leader) = rows.first().cloned() {
+            for row in rows.iter_mut() {
+                row.games_behind = ((leader.wins as f64 - row.wins as f64) + (row.losses as f64 - leader.losses as f64)) / 2.0;
+            }
+        }
+
+        rows
+    }
+
+    /// Returns the standings of every division, in the order the league structure lists them.
+    pub fn division_standings(&self, league_structure: &LeagueStructure) -> Vec<DivisionStandings> {
+        league_structure.leagues.iter()
+            .flat_map(|league| league.divisions.iter().map(move |division| (league, division)))
+            .map(|(league, division)| DivisionStandings {
+                league: league.name.clone(),
+                division: division.name.clone(),
+                rows: self.standings(&division.teams),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played(home_team: &str, visiting_team: &str, home_team_runs: u8, visiting_team_runs: u8) -> ScheduleDay {
+        ScheduleDay {
+            games: vec![ScheduledGame {
+                home_team: home_team.to_string(),
+                visiting_team: visiting_team.to_string(),
+                result: Some(GameResult { home_team_runs, visiting_team_runs }),
+            }],
+        }
+    }
+
+    #[test]
+    fn every_team_plays_a_full_balanced_schedule() {
+        let league = LeagueStructure::default();
+        for seed in 0..5 {
+            let schedule = Schedule::generate(&league, &SeasonConfig::default(), &mut GameRng::seed_from_u64(seed));
+            for team_name in league.team_names() {
+                let games = schedule.days.iter().flat_map(|day| day.games.iter()).filter(|game| game.involves(&team_name));
+                let (home, away) = games.fold((0, 0), |(home, away), game| if game.home_team == team_name { (home + 1, away) } else { (home, away + 1) });
+                assert_eq!((home, away), (81, 81), "{team_name} with seed {seed}");
+            }
+            assert!(schedule.days.iter().all(|day| day.games.iter().flat_map(|game| [&game.home_team, &game.visiting_team]).collect::<HashSet<_>>().len() == day.games.len() * 2));
+        }
+    }
+
+    #[test]
+    fn configs_made_for_a_league_fit_it() {
+        assert_eq!(SeasonConfig::for_league(&LeagueStructure::default()).games_per_team, 162);
+
+        let balanced = LeagueStructure::parse("A,East,Montreal,Expos,MON\nA,East,Boston,Red Sox,BOS\nA,West,San Diego,Padres,SD\nA,West,Seattle,Mariners,SEA").unwrap();
+        let config = SeasonConfig::for_league(&balanced);
+        assert!(config.fits(&balanced));
+        assert_eq!(config.games_per_team, 13 + 5 * 2);
+
+        let lopsided = LeagueStructure::parse("A,East,Montreal,Expos,MON\nA,East,Boston,Red Sox,BOS\nA,West,San Diego,Padres,SD").unwrap();
+        let config = SeasonConfig::for_league(&lopsided);
+        assert!(config.fits(&lopsided));
+        assert_eq!(config.games_per_team, 5 * 2);
+    }
+
+    #[test]
+    fn standings_count_records_games_behind_and_streaks() {
+        let days = vec![
+            played("A", "B", 5, 3),
+            played("B", "C", 2, 1),
+            played("C", "A", 4, 0),
+            played("A", "B", 6, 1),
+            played("B", "A", 3, 2),
+            played("C", "B", 7, 2),
+            ScheduleDay { games: Vec::new() },
+        ];
+        let season = Season { config: SeasonConfig::default(), schedule: Schedule { days }, current_day: 0 };
+        let rows = season.standings(&["B".to_string(), "A".to_string(), "C".to_string()]);
+
+        let summary = rows.iter().map(|row| (row.team.as_str(), row.wins, row.losses, row.run_differential, row.streak)).collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            ("C", 2, 1, 8, Streak::Won(2)),
+            ("A", 2, 2, 2, Streak::Lost(1)),
+            ("B", 2, 3, -10, Streak::Lost(1)),
+        ]);
+        assert_eq!(rows.iter().map(|row| row.games_behind).collect::<Vec<_>>(), vec![0.0, 0.5, 1.0]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::baseball::{BaseballGame, BatterDecision, Count, GameEvent, GameOutcome, GameRng, GameStateSummary, Player, PlayerId, StrikeZoneLocation, Team, TeamSide};
+use crate::box_score::BoxScore;
+use crate::season::GameResult;
+use rand::prelude::*;
+use std::collections::HashMap;
+
+const STARTER_PITCH_LIMIT: u16 = 100;
+const STARTER_RUN_LIMIT: u8 = 5; // a starter who has given up this many is taken out
+const RELIEVER_OUTS: u8 = 3; // relievers other than the closer work an inning at a time
+const RELIEVER_RUN_LIMIT: u8 = 3;
+const CLOSER_INNING: u8 = 9; // the closer comes in from this inning on to protect a close lead
+const CLOSER_MAX_LEAD: i16 = 3;
+
+/// Returns where a computer-managed pitcher aims, given the count. Pitchers behind in the count come into the zone and pitchers ahead of it try to get the batter to chase.
+pub fn ai_pitch_aim(count: &Count, rng: &mut GameRng) -> StrikeZoneLocation {
+    let in_zone_chance = if count.balls == 3 {
+        0.9
+    } else if count.strikes == 2 {
+        0.35
+    } else {
+        0.6
+    };
+
+    if rng.random_bool(in_zone_chance) { StrikeZoneLocation::In } else { StrikeZoneLocation::Out }
+}
+
+/// Returns what a computer-managed batter does, given the count. Batters protect the plate with two strikes and take when ahead.
+pub fn ai_batter_decision(count: &Count, rng: &mut GameRng) -> BatterDecision {
+    let swing_chance = if count.strikes == 2 {
+        0.7
+    } else if count.balls == 3 {
+        0.2
+    } else {
+        0.5
+    };
+
+    if rng.random_bool(swing_chance) { BatterDecision::Swing } else { BatterDecision::Take }
+}
+
+/// Returns the pitcher a computer-managed side brings in before the next batter, if they make a change. Starters come out once they tire or get hit hard and other relievers after an inning's work, while the closer, the first arm in the bullpen, comes in to protect a close lead late in the game. Changes are only made between batters, by the side in the field.
+pub fn ai_pitching_change(team: &Team, side: TeamSide, state: &GameStateSummary, box_score: &BoxScore) -> Option<PlayerId> {
+    let fielding_side = if state.half_inning.top { TeamSide::Home } else { TeamSide::Visiting };
+    if side != fielding_side || state.count.balls != 0 || state.count.strikes != 0 {
+        return None;
+    }
+
+    let team_box = box_score.team_box(side);
+    let line = team_box.pitching.iter().find(|line| line.player == team.current_pitcher)?;
+    let has_pitched = |player: &PlayerId| team_box.pitching.iter().any(|line| line.player == *player);
+    let closer = team.bullpen.first().filter(|closer| !has_pitched(closer));
+
+    let lead = match side {
+        TeamSide::Home => state.home_team_runs as i16 - state.visiting_team_runs as i16,
+        TeamSide::Visiting => state.visiting_team_runs as i16 - state.home_team_runs as i16,
+    };
+    if state.half_inning.number >= CLOSER_INNING && (1..=CLOSER_MAX_LEAD).contains(&lead) && closer.is_some() {
+        return closer.copied();
+    }
+
+    let is_starter = team_box.pitching[0].player == line.player;
+    let is_closer = team.bullpen.first() == Some(&line.player);
+    let is_done = if is_starter {
+        line.pitches >= STARTER_PITCH_LIMIT || line.runs >= STARTER_RUN_LIMIT
+    } else {
+        !is_closer && (line.outs >= RELIEVER_OUTS || line.runs >= RELIEVER_RUN_LIMIT)
+    };
+    if !is_done {
+        return None;
+    }
+
+    team.bullpen.iter().skip(1).find(|reliever| !has_pitched(reliever)).or(closer).copied()
+}
+
+/// A game played to the end without any input or commentary.
+#[derive(Debug, Clone)]
+pub struct SimulatedGame {
+    pub result: GameResult,
+    pub box_score: BoxScore,
+    pub history: Vec<GameEvent>,
+}
+
+/// Plays a whole game between two teams, with both sides managed by the computer.
+pub fn simulate_game(all_players: HashMap<PlayerId, Player>, home_team: Team, visiting_team: Team, rng: &mut GameRng) -> SimulatedGame {
+    let mut box_score = BoxScore::new(&home_team, &visiting_team);
+    let mut baseball_game = BaseballGame::new(all_players, home_team, visiting_team, GameRng::from_rng(rng));
+
+    while baseball_game.state.game_outcome == GameOutcome::Ongoing {
+        let mut prev_state = baseball_game.state_summary();
+        for side in [TeamSide::Home, TeamSide::Visiting] {
+            if let Some(reliever) = ai_pitching_change(baseball_game.team(side), side, &prev_state, &box_score) {
+                baseball_game.change_pitcher(side, reliever);
+                prev_state = baseball_game.state_summary();
+            }
+        }
+        let pitch_aim = ai_pitch_aim(&prev_state.count, rng);
+        let batter_decision = ai_batter_decision(&prev_state.count, rng);
+        let events_summary = baseball_game.simulate_pitch(Some(pitch_aim), Some(batter_decision));
+        box_score.record(&prev_state, &baseball_game.state_summary(), &events_summary);
+    }
+
+    SimulatedGame {
+        result: baseball_game.result(),
+        box_score,
+        history: baseball_game.history,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{players, team};
+    use rand::SeedableRng;
+
+    #[test]
+    fn simulated_games_come_with_a_full_box_score() {
+        let mut rng = GameRng::seed_from_u64(4);
+        let game = simulate_game(players(), team("home", 0), team("visiting", 100), &mut rng);
+        assert_ne!(game.result.home_team_runs, game.result.visiting_team_runs);
+
+        for (team_box, runs, outs_against) in [
+            (&game.box_score.home, game.result.home_team_runs, &game.box_score.visiting),
+            (&game.box_score.visiting, game.result.visiting_team_runs, &game.box_score.home),
+        ] {
+            assert_eq!(team_box.line.runs, runs);
+            assert_eq!(team_box.batting.iter().map(|line| line.runs).sum::<u8>(), runs);
+            assert_eq!(team_box.batting.iter().map(|line| line.hits).sum::<u8>(), team_box.line.hits);
+            assert_eq!(outs_against.pitching.iter().map(|line| line.runs).sum::<u8>(), runs);
+            assert!(team_box.pitching.iter().map(|line| line.outs as u32).sum::<u32>() >= 24);
+        }
+        assert!(game.box_score.decisions().is_some());
+        assert!(!game.history.is_empty());
+    }
+}
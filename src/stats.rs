@@ -0,0 +1,492 @@
+use crate::analytics::{self, Play, PlayerValue, RunExpectancy};
+use crate::baseball::{GameEvent, Player, PlayerId};
+use crate::box_score::{BoxScore, Decisions, TeamBox};
+use crate::league::LeagueStructure;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const QUALIFYING_PLATE_APPEARANCES_PER_GAME: f64 = 3.1;
+const QUALIFYING_OUTS_PER_GAME: f64 = 3.0;
+const CAREER_QUALIFYING_PLATE_APPEARANCES: u32 = 500;
+const CAREER_QUALIFYING_OUTS: u32 = 300;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BattingStats {
+    pub games: u32,
+    pub at_bats: u32,
+    pub runs: u32,
+    pub hits: u32,
+    pub doubles: u32,
+    pub triples: u32,
+    pub home_runs: u32,
+    pub runs_batted_in: u32,
+    pub walks: u32,
+    pub strikeouts: u32,
+    pub stolen_bases: u32, // nobody steals yet, so this stays at 0
+}
+
+impl BattingStats {
+    pub fn plate_appearances(&self) -> u32 {
+        self.at_bats + self.walks
+    }
+
+    pub fn total_bases(&self) -> u32 {
+        self.hits + self.doubles + 2 * self.triples + 3 * self.home_runs
+    }
+
+    pub fn batting_average(&self) -> f64 {
+        ratio(self.hits, self.at_bats)
+    }
+
+    pub fn on_base_percentage(&self) -> f64 {
+        ratio(self.hits + self.walks, self.plate_appearances())
+    }
+
+    pub fn slugging_percentage(&self) -> f64 {
+        ratio(self.total_bases(), self.at_bats)
+    }
+
+    pub fn on_base_plus_slugging(&self) -> f64 {
+        self.on_base_percentage() + self.slugging_percentage()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PitchingStats {
+    pub games: u32,
+    pub outs: u32,
+    pub hits: u32,
+    pub runs: u32,
+    pub earned_runs: u32,
+    pub walks: u32,
+    pub strikeouts: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub saves: u32,
+    #[serde(default)] // stat books saved before holds were kept have none
+    pub holds: u32,
+}
+
+impl PitchingStats {
+    pub fn earned_run_average(&self) -> f64 {
+        27.0 * ratio(self.earned_runs, self.outs)
+    }
+
+    pub fn walks_and_hits_per_inning(&self) -> f64 {
+        3.0 * ratio(self.walks + self.hits, self.outs)
+    }
+
+    pub fn strikeouts_per_nine(&self) -> f64 {
+        27.0 * ratio(self.strikeouts, self.outs)
+    }
+}
+
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 }
+}
+
+/// A player's numbers over a season or a career. The name and team are kept with them so they still read right after the player has left the league.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub name: String,
+    pub team: String, // the last team they played for
+    pub batting: BattingStats,
+    pub pitching: PitchingStats,
+    #[serde(default)] // stat books saved before it was kept start from nothing
+    pub value: PlayerValue, // RE24 and WPA over finished seasons, each against that season's final run expectancy, so only kept for careers; see `StatBook::season_values` for the season under way
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum StatCategory {
+    BattingAverage,
+    OnBasePercentage,
+    SluggingPercentage,
+    OnBasePlusSlugging,
+    HomeRuns,
+    RunsBattedIn,
+    EarnedRunAverage,
+    WalksAndHitsPerInning,
+    StrikeoutsPerNine,
+    Wins,
+    Saves,
+    Holds,
+    Strikeouts,
+}
+
+impl StatCategory {
+    pub const ALL: [StatCategory; 13] = [
+        StatCategory::BattingAverage,
+        StatCategory::OnBasePercentage,
+        StatCategory::SluggingPercentage,
+        StatCategory::OnBasePlusSlugging,
+        StatCategory::HomeRuns,
+        StatCategory::RunsBattedIn,
+        StatCategory::EarnedRunAverage,
+        StatCategory::WalksAndHitsPerInning,
+        StatCategory::StrikeoutsPerNine,
+        StatCategory::Wins,
+        StatCategory::Saves,
+        StatCategory::Holds,
+        StatCategory::Strikeouts,
+    ];
+
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            StatCategory::BattingAverage => "AVG",
+            StatCategory::OnBasePercentage => "OBP",
+            StatCategory::SluggingPercentage => "SLG",
+            StatCategory::OnBasePlusSlugging => "OPS",
+            StatCategory::HomeRuns => "HR",
+            StatCategory::RunsBattedIn => "RBI",
+            StatCategory::EarnedRunAverage => "ERA",
+            StatCategory::WalksAndHitsPerInning => "WHIP",
+            StatCategory::StrikeoutsPerNine => "K/9",
+            StatCategory::Wins => "W",
+            StatCategory::Saves => "SV",
+            StatCategory::Holds => "HLD",
+            StatCategory::Strikeouts => "K",
+        }
+    }
+
+    pub fn is_pitching(&self) -> bool {
+        matches!(
+            self,
+            StatCategory::EarnedRunAverage | StatCategory::WalksAndHitsPerInning | StatCategory::StrikeoutsPerNine
+                | StatCategory::Wins | StatCategory::Saves | StatCategory::Holds | StatCategory::Strikeouts,
+        )
+    }
+
+    /// Returns whether the category is a rate, which only counts for players with enough playing time to qualify.
+    pub fn is_rate(&self) -> bool {
+        matches!(
+            self,
+            StatCategory::BattingAverage | StatCategory::OnBasePercentage | StatCategory::SluggingPercentage
+                | StatCategory::OnBasePlusSlugging | StatCategory::EarnedRunAverage | StatCategory::WalksAndHitsPerInning
+                | StatCategory::StrikeoutsPerNine,
+        )
+    }
+
+    pub fn lower_is_better(&self) -> bool {
+        matches!(self, StatCategory::EarnedRunAverage | StatCategory::WalksAndHitsPerInning)
+    }
+
+    pub fn value(&self, stats: &PlayerStats) -> f64 {
+        let (batting, pitching) = (&stats.batting, &stats.pitching);
+        match self {
+            StatCategory::BattingAverage => batting.batting_average(),
+            StatCategory::OnBasePercentage => batting.on_base_percentage(),
+            StatCategory::SluggingPercentage => batting.slugging_percentage(),
+            StatCategory::OnBasePlusSlugging => batting.on_base_plus_slugging(),
+            StatCategory::HomeRuns => batting.home_runs as f64,
+            StatCategory::RunsBattedIn => batting.runs_batted_in as f64,
+            StatCategory::EarnedRunAverage => pitching.earned_run_average(),
+            StatCategory::WalksAndHitsPerInning => pitching.walks_and_hits_per_inning(),
+            StatCategory::StrikeoutsPerNine => pitching.strikeouts_per_nine(),
+            StatCategory::Wins => pitching.wins as f64,
+            StatCategory::Saves => pitching.saves as f64,
+            StatCategory::Holds => pitching.holds as f64,
+            StatCategory::Strikeouts => pitching.strikeouts as f64,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StatScope {
+    Season,
+    Career,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AwardKind {
+    MostValuablePlayer,
+    CyYoung,
+}
+
+impl AwardKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AwardKind::MostValuablePlayer => "Most Valuable Player",
+            AwardKind::CyYoung => "Cy Young Award",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Award {
+    pub season: u32,
+    pub kind: AwardKind,
+    pub league: String,
+    pub player: PlayerId,
+    pub description: String,
+}
+
+/// Every player's numbers for the current season and their whole career, built up from box scores, along with the awards handed out so far and the season's run expectancy and plays.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatBook {
+    #[serde(default)] // stat books saved before it was kept can't be matched to a game
+    pub seed: Option<u64>, // of the game the stats were kept for
+    pub team_games: HashMap<String, u32>, // this season
+    pub season: HashMap<PlayerId, PlayerStats>,
+    pub career: HashMap<PlayerId, PlayerStats>,
+    pub awards: Vec<Award>,
+    #[serde(default)]
+    pub run_expectancy: RunExpectancy, // this season
+    #[serde(default)]
+    pub plays: Vec<Play>, // this season's, joined into plate appearances
+}
+
+impl StatBook {
+    /// Starts an empty stat book for the game played from the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { seed: Some(seed), ..Self::default() }
+    }
+
+    /// Adds a finished game's box score to everyone's season and career numbers, and its plays to the season's run expectancy and plays.
+    pub fn record_game(&mut self, box_score: &BoxScore, history: &[GameEvent], all_players: &HashMap<PlayerId, Player>) {
+        let decisions = box_score.decisions();
+        for (team, team_box) in [(&box_score.home_team, &box_score.home), (&box_score.visiting_team, &box_score.visiting)] {
+            *self.team_games.entry(team.clone()).or_insert(0) += 1;
+            for stats in [&mut self.season, &mut self.career] {
+                record_team_box(stats, team, team_box, decisions.as_ref(), all_players);
+            }
+        }
+        self.run_expectancy.record_history(history);
+        self.plays.extend(analytics::join_plays(analytics::plays(history)));
+    }
+
+    /// Returns every player's RE24 and WPA so far this season, with every play worked out against the season's run expectancy as it stands now.
+    pub fn season_values(&self) -> HashMap<PlayerId, PlayerValue> {
+        analytics::player_values(&analytics::evaluate(&self.plays, &self.run_expectancy))
+    }
+
+    fn stats(&self, scope: StatScope) -> &HashMap<PlayerId, PlayerStats> {
+        match scope {
+            StatScope::Season => &self.season,
+            StatScope::Career => &self.career,
+        }
+    }
+
+    /// Returns whether the player has played enough to count for rate stats in the given category: 3.1 plate appearances or an inning pitched per team game over a season.
+    pub fn qualifies(&self, stats: &PlayerStats, category: StatCategory, scope: StatScope) -> bool {
+        let team_games = self.team_games.get(&stats.team).copied().unwrap_or(0) as f64;
+        match (scope, category.is_pitching()) {
+            (StatScope::Season, false) => stats.batting.plate_appearances() as f64 >= QUALIFYING_PLATE_APPEARANCES_PER_GAME * team_games,
+            (StatScope::Season, true) => stats.pitching.outs as f64 >= QUALIFYING_OUTS_PER_GAME * team_games,
+            (StatScope::Career, false) => stats.batting.plate_appearances() >= CAREER_QUALIFYING_PLATE_APPEARANCES,
+            (StatScope::Career, true) => stats.pitching.outs >= CAREER_QUALIFYING_OUTS,
+        }
+    }
+
+    /// Returns up to `count` of the best players in the given category, best first, with their values. Rate stats only count qualified players.
+    pub fn leaders(&self, category: StatCategory, scope: StatScope, count: usize) -> Vec<(PlayerId, f64)> {
+        let mut leaders = self.stats(scope).iter()
+            .filter(|(_, stats)| if category.is_pitching() { stats.pitching.games > 0 } else { stats.batting.games > 0 })
+            .filter(|(_, stats)| !category.is_rate() || self.qualifies(stats, category, scope))
+            .map(|(player, stats)| (*player, category.value(stats)))
+            .collect::<Vec<_>>();
+        leaders.sort_by(|(a_player, a), (b_player, b)| {
+            let ordering = if category.lower_is_better() { a.total_cmp(b) } else { b.total_cmp(a) };
+            ordering.then(a_player.cmp(b_player))
+        });
+        leaders.truncate(count);
+
+        leaders
+    }
+
+    /// Returns who should win each award in each league this season: the qualified hitter with the best OPS is MVP and the qualified pitcher with the best ERA takes the Cy Young.
+    pub fn award_winners(&self, league: &LeagueStructure) -> Vec<(AwardKind, String, PlayerId)> {
+        let mut winners = Vec::new();
+        for league in league.leagues.iter() {
+            let in_league = |player: &PlayerId| league.teams().any(|team| *team == self.season.get(player).unwrap().team);
+            for (kind, category) in [(AwardKind::MostValuablePlayer, StatCategory::OnBasePlusSlugging), (AwardKind::CyYoung, StatCategory::EarnedRunAverage)] {
+                let winner = self.leaders(category, StatScope::Season, usize::MAX).into_iter().find(|(player, _)| in_league(player));
+                if let Some((player, _)) = winner {
+                    winners.push((kind, league.name.clone(), player));
+                }
+            }
+        }
+
+        winners
+    }
+
+    /// Adds the season's RE24 and WPA to careers, then clears the season's numbers, leaving careers and awards as they are.
+    pub fn start_new_season(&mut self) {
+        for (player, value) in self.season_values() {
+            self.career.entry(player).or_default().value.add(&value);
+        }
+        self.team_games.clear();
+        self.season.clear();
+        self.run_expectancy = RunExpectancy::default();
+        self.plays.clear();
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// Returns the player's stats, starting them if need be, with their name and team brought up to date.
+fn player_entry<'a>(stats: &'a mut HashMap<PlayerId, PlayerStats>, player: PlayerId, team: &str, all_players: &HashMap<PlayerId, Player>) -> &'a mut PlayerStats {
+    let player_stats = stats.entry(player).or_default();
+    if let Some(player) = all_players.get(&player) {
+        player_stats.name = player.name.clone();
+    }
+    player_stats.team = team.to_string();
+    player_stats
+}
+
+fn record_team_box(
+    stats: &mut HashMap<PlayerId, PlayerStats>,
+    team: &str,
+    team_box: &TeamBox,
+    decisions: Option<&Decisions>,
+    all_players: &HashMap<PlayerId, Player>,
+) {
+    for line in team_box.batting.iter() {
+        let batting = &mut player_entry(stats, line.player, team, all_players).batting;
+        batting.games += 1;
+        batting.at_bats += line.at_bats as u32;
+        batting.runs += line.runs as u32;
+        batting.hits += line.hits as u32;
+        batting.doubles += line.doubles as u32;
+        batting.triples += line.triples as u32;
+        batting.home_runs += line.home_runs as u32;
+        batting.runs_batted_in += line.runs_batted_in as u32;
+        batting.walks += line.walks as u32;
+        batting.strikeouts += line.strikeouts as u32;
+    }
+
+    for line in team_box.pitching.iter() {
+        let pitching = &mut player_entry(stats, line.player, team, all_players).pitching;
+        pitching.games += 1;
+        pitching.outs += line.outs as u32;
+        pitching.hits += line.hits as u32;
+        pitching.runs += line.runs as u32;
+        pitching.earned_runs += line.earned_runs as u32;
+        pitching.walks += line.walks as u32;
+        pitching.strikeouts += line.strikeouts as u32;
+        if let Some(decisions) = decisions {
+            pitching.wins += (line.player == decisions.win) as u32;
+            pitching.losses += (line.player == decisions.loss) as u32;
+            pitching.saves += (decisions.save == Some(line.player)) as u32;
+            pitching.holds += decisions.holds.contains(&line.player) as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::GameRng;
+    use crate::sim;
+    use crate::test_support::{players, team};
+    use rand::SeedableRng;
+
+    fn batter(team: &str, at_bats: u32, hits: u32, home_runs: u32) -> PlayerStats {
+        PlayerStats {
+            name: String::new(),
+            team: team.to_string(),
+            batting: BattingStats { games: 10, at_bats, hits, home_runs, ..BattingStats::default() },
+            pitching: PitchingStats::default(),
+            value: PlayerValue::default(),
+        }
+    }
+
+    fn pitcher(team: &str, outs: u32, earned_runs: u32) -> PlayerStats {
+        PlayerStats {
+            name: String::new(),
+            team: team.to_string(),
+            batting: BattingStats::default(),
+            pitching: PitchingStats { games: 10, outs, earned_runs, ..PitchingStats::default() },
+            value: PlayerValue::default(),
+        }
+    }
+
+    #[test]
+    fn rate_stats_are_worked_out_from_the_counts() {
+        let batting = BattingStats { at_bats: 10, hits: 3, doubles: 1, home_runs: 1, walks: 2, ..BattingStats::default() };
+        assert_eq!(batting.batting_average(), 0.3);
+        assert_eq!(batting.on_base_percentage(), 5.0 / 12.0);
+        assert_eq!(batting.slugging_percentage(), 0.7);
+        assert_eq!(BattingStats::default().on_base_plus_slugging(), 0.0);
+
+        let pitching = PitchingStats { outs: 27, earned_runs: 3, hits: 7, walks: 2, strikeouts: 9, ..PitchingStats::default() };
+        assert_eq!(pitching.earned_run_average(), 3.0);
+        assert_eq!(pitching.walks_and_hits_per_inning(), 1.0);
+        assert_eq!(pitching.strikeouts_per_nine(), 9.0);
+    }
+
+    #[test]
+    fn only_qualified_players_lead_rate_stats() {
+        let mut stat_book = StatBook::new(0);
+        stat_book.team_games.insert("A".to_string(), 10);
+        stat_book.season.insert(PlayerId(1), batter("A", 31, 9, 2));
+        stat_book.season.insert(PlayerId(2), batter("A", 30, 15, 1)); // a plate appearance short of qualifying
+        stat_book.season.insert(PlayerId(3), batter("A", 40, 14, 2));
+        stat_book.season.insert(PlayerId(4), pitcher("A", 30, 5));
+        stat_book.season.insert(PlayerId(5), pitcher("A", 29, 0));
+        stat_book.season.insert(PlayerId(6), pitcher("A", 60, 4));
+
+        let leaders = |category| stat_book.leaders(category, StatScope::Season, 5).into_iter().map(|(player, _)| player.0).collect::<Vec<_>>();
+        assert_eq!(leaders(StatCategory::BattingAverage), [3, 1]);
+        assert_eq!(leaders(StatCategory::HomeRuns), [1, 3, 2]);
+        assert_eq!(leaders(StatCategory::EarnedRunAverage), [6, 4]);
+        assert_eq!(stat_book.leaders(StatCategory::HomeRuns, StatScope::Season, 1), [(PlayerId(1), 2.0)]);
+        assert!(stat_book.leaders(StatCategory::BattingAverage, StatScope::Career, 5).is_empty());
+    }
+
+    #[test]
+    fn saved_stat_books_load_back_the_same() {
+        let mut stat_book = StatBook::new(7);
+        stat_book.team_games.insert("A".to_string(), 10);
+        stat_book.season.insert(PlayerId(1), batter("A", 31, 9, 2));
+        stat_book.career.insert(PlayerId(4), pitcher("A", 30, 5));
+
+        let path = std::env::temp_dir().join(format!("idbb-stats-{}.json", std::process::id()));
+        stat_book.save(&path).unwrap();
+        let loaded = StatBook::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.seed, Some(7));
+        assert_eq!(loaded.team_games, stat_book.team_games);
+        assert_eq!(format!("{:?}", loaded.season.get(&PlayerId(1))), format!("{:?}", stat_book.season.get(&PlayerId(1))));
+        assert_eq!(format!("{:?}", loaded.career.get(&PlayerId(4))), format!("{:?}", stat_book.career.get(&PlayerId(4))));
+    }
+
+    #[test]
+    fn season_re24_and_wpa_are_worked_out_against_the_final_run_expectancy() {
+        let all_players = players();
+        let mut rng = GameRng::seed_from_u64(3);
+        let games = (0..3).map(|_| sim::simulate_game(all_players.clone(), team("home", 0), team("visiting", 100), &mut rng)).collect::<Vec<_>>();
+
+        let mut stat_book = StatBook::new(3);
+        for game in games.iter() {
+            stat_book.record_game(&game.box_score, &game.history, &all_players);
+        }
+        let all_plays = games.iter().flat_map(|game| game.history.iter().cloned()).collect::<Vec<_>>();
+        let expected = analytics::player_values(&analytics::evaluate_plays(&all_plays, &stat_book.run_expectancy));
+        assert!(stat_book.plays.len() < analytics::plays(&all_plays).len());
+
+        let is_close = |a: &PlayerValue, b: &PlayerValue| {
+            [(a.batting_re24, b.batting_re24), (a.batting_wpa, b.batting_wpa), (a.pitching_re24, b.pitching_re24), (a.pitching_wpa, b.pitching_wpa)]
+                .iter()
+                .all(|(a, b)| (a - b).abs() < 1e-9)
+        };
+        let season_values = stat_book.season_values();
+        assert_eq!(season_values.len(), expected.len());
+        for (player, value) in expected.iter() {
+            assert!(is_close(season_values.get(player).unwrap(), value), "{player:?}");
+        }
+
+        stat_book.start_new_season();
+        assert!(stat_book.season.is_empty() && stat_book.plays.is_empty());
+        assert!(is_close(&stat_book.career.get(&PlayerId(9)).unwrap().value, expected.get(&PlayerId(9)).unwrap()));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::baseball::{Base, GameRng, Player, PlayerId, PlayerMetrics, Position, Runner, RunnerAdvancement, Team};
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+/// Returns a team whose players are numbered from `first_id`: hitters `first_id` to `first_id + 8`, with `first_id + 9` starting and `first_id + 10` and `first_id + 11` in the bullpen.
+pub fn team(name: &str, first_id: u32) -> Team {
+    let id = |i: u32| PlayerId(first_id + i);
+    Team {
+        name: name.to_string(),
+        batting_order: std::array::from_fn(|i| id(i as u32)),
+        all_pitchers: vec![id(9)],
+        rotation_index: 0,
+        days_of_rest: HashMap::new(),
+        current_pitcher: id(9),
+        fielders: std::array::from_fn(|i| id(i as u32 + 1)),
+        bench: Vec::new(),
+        bullpen: vec![id(10), id(11)],
+    }
+}
+
+/// Returns players to fill the teams made by `team` from 0 and 100, with random metrics.
+pub fn players() -> HashMap<PlayerId, Player> {
+    let mut rng = GameRng::seed_from_u64(0);
+    (0..12).chain(100..112)
+        .map(|id| (PlayerId(id), Player {
+            id: PlayerId(id),
+            name: format!("Player {id}"),
+            position: Position::Pitcher,
+            age: 27,
+            potential: 0.5,
+            contract: None,
+            metrics: PlayerMetrics::random(&mut rng),
+        }))
+        .collect()
+}
+
+pub fn runner(player: u32, responsible_pitcher: u32) -> Option<Runner> {
+    Some(Runner { player: PlayerId(player), responsible_pitcher: PlayerId(responsible_pitcher), earned: true })
+}
+
+pub fn advance(player: u32, from_base: Base, to_base: Option<Base>) -> RunnerAdvancement {
+    RunnerAdvancement { player: PlayerId(player), from_base, to_base }
+}
@@ -1,57 +1,523 @@
-use crate::baseball::{AtBatOutcome, Base, BatterDecision, BattingOutcome, Count, EventsSummary, GameStateSummary, Player, RunnerAdvancement};
+use crate::baseball::{AtBatOutcome, Base, BatterDecision, BattingOutcome, Contract, DemonicEventKind, EventsSummary, GameOutcome, GameStateSummary, Player, PlayerId, Position, RunnerAdvancement, Team, TeamSide};
+use crate::batch::{BatchStat, BatchTotals};
+use crate::box_score::{BoxScore, TeamBox};
+use crate::demon::DemonPowerEffect;
+use crate::postseason::{Postseason, Series};
+use crate::season::{DivisionStandings, StandingsRow, Streak};
+use crate::stats::{AwardKind, PlayerStats, StatCategory};
+use crate::transactions::TransactionRecord;
+use crate::win_probability::WinProbabilityModel;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+const MIN_AT_BATS_TO_QUOTE_AVERAGE: u32 = 20;
+
+/// How much of the game goes by between the user's inputs, finest first.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 pub enum Granularity {
     Pitch,
+    AtBat,
     HalfInning,
+    Inning,
+    Game,
 }
 
 impl Granularity {
-    fn calculate_tension(state_summary: &GameStateSummary) -> usize {
-        let inning = state_summary.half_inning.number as usize;
-        let run_diff = (state_summary.home_team_runs as i8 - state_summary.visiting_team_runs as i8).abs() as usize;
+    pub const ALL: [Granularity; 5] = [
+        Granularity::Pitch,
+        Granularity::AtBat,
+        Granularity::HalfInning,
+        Granularity::Inning,
+        Granularity::Game,
+    ];
+
+    /// Returns the granularity play goes at in the given state: the finest one whose threshold the leverage index reaches.
+    pub fn from_state_summary(state_summary: &GameStateSummary, thresholds: &GranularityThresholds, win_probability: &WinProbabilityModel) -> Self {
+        let leverage = win_probability.leverage_index(state_summary);
 
-        if run_diff == 0 {
-            f32::INFINITY as usize
+        if leverage >= thresholds.pitch {
+            Self::Pitch
+        } else if leverage >= thresholds.at_bat {
+            Self::AtBat
+        } else if leverage >= thresholds.half_inning {
+            Self::HalfInning
+        } else if leverage >= thresholds.inning {
+            Self::Inning
         } else {
-            inning / run_diff
+            Self::Game
         }
     }
 
-    pub fn from_state_summary(state_summary: &GameStateSummary) -> Self {
-        let tension = Self::calculate_tension(state_summary);
+    /// Returns whether a stretch of play at this granularity, starting from the first state, is over by the second.
+    pub fn is_over(&self, start: &GameStateSummary, state: &GameStateSummary) -> bool {
+        let same_half_inning = start.half_inning.number == state.half_inning.number && start.half_inning.top == state.half_inning.top;
+        match self {
+            Granularity::Pitch => true,
+            Granularity::AtBat => !same_half_inning || start.batter != state.batter,
+            Granularity::HalfInning => !same_half_inning,
+            Granularity::Inning => start.half_inning.number != state.half_inning.number,
+            Granularity::Game => false,
+        }
+    }
+}
 
-        if tension > 3 {
-            Self::HalfInning
+/// The leverage index each granularity finer than a whole game needs, where 1 is an average plate appearance and higher is tenser. Each threshold should be no higher than the one before it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GranularityThresholds {
+    pub pitch: f64,
+    pub at_bat: f64,
+    pub half_inning: f64,
+    pub inning: f64,
+}
+
+impl GranularityThresholds {
+    /// Returns whether every threshold is a number no higher than the one for the granularity finer than it, and none is below zero.
+    pub fn are_in_order(&self) -> bool {
+        [self.pitch, self.at_bat, self.half_inning, self.inning, 0.0].windows(2).all(|pair| pair[0] >= pair[1])
+    }
+
+    /// Returns every threshold multiplied by `factor`: below 1 to follow more of the game closely, above 1 to follow less of it.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self { pitch: self.pitch * factor, at_bat: self.at_bat * factor, half_inning: self.half_inning * factor, inning: self.inning * factor }
+    }
+
+    /// Returns the thresholds the user can pick from: closer than the default, the default, and looser.
+    pub fn presets() -> [Self; 3] {
+        [0.5, 1.0, 2.0].map(|factor| Self::default().scaled(factor))
+    }
+}
+
+impl Default for GranularityThresholds {
+    fn default() -> Self {
+        Self { pitch: 1.5, at_bat: 0.9, half_inning: 0.4, inning: 0.1 }
+    }
+}
+
+/// How the user wants the game's granularity chosen.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GranularitySettings {
+    pub thresholds: GranularityThresholds,
+    pub forced: Option<Granularity>, // Some to play at this granularity whatever the leverage
+}
+
+impl GranularitySettings {
+    pub fn granularity(&self, state_summary: &GameStateSummary, win_probability: &WinProbabilityModel) -> Granularity {
+        self.forced.unwrap_or_else(|| Granularity::from_state_summary(state_summary, &self.thresholds, win_probability))
+    }
+}
+
+fn describe_granularity(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Pitch => "pitch by pitch",
+        Granularity::AtBat => "an at bat at a time",
+        Granularity::HalfInning => "a half-inning at a time",
+        Granularity::Inning => "an inning at a time",
+        Granularity::Game => "a game at a time",
+    }
+}
+
+pub fn describe_granularity_settings(settings: &GranularitySettings) -> String {
+    if let Some(granularity) = settings.forced {
+        return format!("Play goes {} whatever the leverage.", describe_granularity(granularity));
+    }
+
+    let thresholds = &settings.thresholds;
+    format!(
+        "Play goes {} from a leverage index of {}, {} from {}, {} from {}, {} from {}, and {} below that.",
+        describe_granularity(Granularity::Pitch),
+        thresholds.pitch,
+        describe_granularity(Granularity::AtBat),
+        thresholds.at_bat,
+        describe_granularity(Granularity::HalfInning),
+        thresholds.half_inning,
+        describe_granularity(Granularity::Inning),
+        thresholds.inning,
+        describe_granularity(Granularity::Game),
+    )
+}
+
+pub fn describe_prayer() -> String {
+    "The clubhouse lights go out, and somewhere beneath the visitors' dugout, something is listening.".to_string()
+}
+
+pub fn describe_blood_sacrifice(victim: &str, replacement: &str) -> String {
+    format!(
+        "{victim} walks out to the mound at midnight and does not walk back. Rorzagoth is pleased. {replacement} has been signed to fill the spot on the roster.",
+    )
+}
+
+/// Lists names the way a sentence would: "A", "A and B", "A, B and C".
+fn list_names(names: &[&str]) -> String {
+    match names {
+        [] => "nobody".to_string(),
+        [name] => name.to_string(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+/// Ends a sentence with a full stop, unless it already ends with one, as it does after "Jr.".
+fn end_sentence(sentence: String) -> String {
+    if sentence.ends_with('.') { sentence } else { sentence + "." }
+}
+
+fn describe_salary(salary: u32) -> String {
+    if salary >= 1000 {
+        format!("${:.1} million", salary as f64 / 1000.0)
+    } else {
+        format!("${salary},000")
+    }
+}
+
+pub fn describe_trade(team: &str, other_team: &str, sent: &[&str], received: &[&str]) -> String {
+    end_sentence(format!("The {team} traded {} to the {other_team} for {}", list_names(sent), list_names(received)))
+}
+
+pub fn describe_signing(team: &str, player: &str, contract: &Contract) -> String {
+    let years = if contract.years == 1 { "1 year".to_string() } else { format!("{} years", contract.years) };
+    format!("The {team} signed {player} for {years} at {} a year.", describe_salary(contract.salary))
+}
+
+pub fn describe_release(team: &str, player: &str) -> String {
+    end_sentence(format!("The {team} released {player}"))
+}
+
+pub fn describe_retirement(team: &str, player: &str, age: u32) -> String {
+    format!("{player} of the {team} retired at {age}.")
+}
+
+pub fn describe_trade_draft(team: &str, offered: &[&str], requested: &[&str]) -> String {
+    end_sentence(format!("Talking trade with the {team}. Offering {} for {}", list_names(offered), list_names(requested)))
+}
+
+pub fn describe_trade_response(team: &str, accepted: bool) -> String {
+    if accepted {
+        format!("The {team} have accepted. The paperwork goes through before anyone can change their mind.")
+    } else {
+        format!("The {team} aren't interested.")
+    }
+}
+
+pub fn describe_transactions(records: &[TransactionRecord]) -> String {
+    if records.is_empty() {
+        return "No transactions yet.".to_string();
+    }
+
+    records.iter()
+        .map(|record| format!("Season {}, day {}: {}", record.season, record.day + 1, record.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn describe_new_season(season: u32, retired: &[&str], rookies: usize) -> String {
+    let retirements = match retired.len() {
+        0 => "Nobody retired".to_string(),
+        1 => format!("{} retired", retired[0]),
+        n => format!("{n} players retired, including {}", list_names(&retired[..n.min(3)])),
+    };
+    format!("Season {season} is about to begin. {retirements}, and {rookies} rookies joined the free agent pool.")
+}
+
+pub fn describe_next_game(day: usize, home_team: &str, visiting_team: &str) -> String {
+    format!("Day {}: the {visiting_team} visit the {home_team}.", day + 1)
+}
+
+pub fn describe_probable_starter(opponent: &str, starter: &str) -> String {
+    end_sentence(format!("The {opponent} are expected to start {starter}"))
+}
+
+pub fn describe_lineup(team: &Team, all_players: &HashMap<PlayerId, Player>, opponent: &str, opponent_starter: &str) -> String {
+    let player_name = |player: &PlayerId| all_players.get(player).map_or("(gone)", |player| player.name.as_str());
+
+    let mut lines = vec![format!("{} lineup:", team.name)];
+    for (i, player) in team.batting_order.iter().enumerate() {
+        let position = match team.fielders.iter().position(|fielder| fielder == player) {
+            Some(index) => Position::FIELDING[index].abbreviation(),
+            None => "DH",
+        };
+        lines.push(format!("{}. {} ({position})", i + 1, player_name(player)));
+    }
+    let describe_rest = |pitcher: &PlayerId| match team.days_of_rest(*pitcher) {
+        1 => "1 day's rest".to_string(),
+        days => format!("{days} days' rest"),
+    };
+    lines.push(format!("Starting pitcher: {} ({})", player_name(&team.current_pitcher), describe_rest(&team.current_pitcher)));
+    let rotation = (0..team.all_pitchers.len())
+        .map(|offset| &team.all_pitchers[(team.rotation_index + offset) % team.all_pitchers.len()])
+        .map(|pitcher| format!("{} ({})", player_name(pitcher), describe_rest(pitcher)))
+        .collect::<Vec<_>>();
+    lines.push(format!("Rotation, in turn: {}", rotation.join(", ")));
+    lines.push(format!("Bench: {}", team.bench.iter().map(player_name).collect::<Vec<_>>().join(", ")));
+    lines.push(describe_probable_starter(opponent, opponent_starter));
+
+    lines.join("\n")
+}
+
+pub fn describe_postseason_game(round_name: &str, series: &Series, home_team: &str, visiting_team: &str) -> String {
+    let higher_seed_wins = series.wins(&series.higher_seed);
+    let lower_seed_wins = series.wins(&series.lower_seed);
+    let series_status = if higher_seed_wins == lower_seed_wins {
+        format!("The series is tied {higher_seed_wins}-{lower_seed_wins}.")
+    } else if higher_seed_wins > lower_seed_wins {
+        format!("The {} lead the series {higher_seed_wins}-{lower_seed_wins}.", series.higher_seed)
+    } else {
+        format!("The {} lead the series {lower_seed_wins}-{higher_seed_wins}.", series.lower_seed)
+    };
+
+    format!(
+        "Game {} of the {round_name}: the {visiting_team} visit the {home_team}. {series_status}",
+        series.games.len() + 1,
+    )
+}
+
+pub fn describe_postseason_over(postseason: &Postseason, own_team: &str) -> String {
+    let champion = postseason.champion().unwrap();
+    let mut sentences = vec![format!("The {champion} have won the World Series.")];
+
+    if champion == own_team {
+        sentences.push("Rorzagoth's bargain has been kept.".to_string());
+    } else if !postseason.made_postseason(own_team) {
+        sentences.push(format!("The {own_team} didn't make the postseason."));
+    } else {
+        let knocked_out_in = postseason.rounds.iter().enumerate()
+            .find(|(_, round)| round.iter().any(|series| series.loser() == Some(own_team)))
+            .map(|(round, _)| postseason.round_name(round))
+            .unwrap();
+        sentences.push(format!("The {own_team} were knocked out in the {knocked_out_in}."));
+    }
+
+    sentences.join(" ")
+}
+
+pub fn describe_standings(standings: &[DivisionStandings]) -> String {
+    let mut lines = Vec::new();
+    for division_standings in standings {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "{:<24} {:>4} {:>4} {:>6} {:>6} {:>6}",
+            format!("{} {}", division_standings.league, division_standings.division),
+            "W", "L", "GB", "RD", "STRK",
+        ));
+        lines.extend(division_standings.rows.iter().map(describe_standings_row));
+    }
+
+    lines.join("\n")
+}
+
+fn describe_standings_row(row: &StandingsRow) -> String {
+    let games_behind = if row.games_behind == 0.0 { "-".to_string() } else { format!("{:.1}", row.games_behind) };
+    let streak = match row.streak {
+        Streak::Won(n) => format!("W{n}"),
+        Streak::Lost(n) => format!("L{n}"),
+        Streak::None => "-".to_string(),
+    };
+
+    format!(
+        "{:<24} {:>4} {:>4} {:>6} {:>+6} {:>6}",
+        row.team,
+        row.wins,
+        row.losses,
+        games_behind,
+        row.run_differential,
+        streak,
+    )
+}
+
+/// Writes out a box score: the line score by inning, then each side's batters and pitchers.
+pub fn describe_box_score(box_score: &BoxScore, all_players: &HashMap<PlayerId, Player>) -> String {
+    let innings = box_score.visiting.line.runs_by_inning.len().max(box_score.home.line.runs_by_inning.len());
+    let line_score = |team: &str, team_box: &TeamBox| {
+        let runs_by_inning = (0..innings)
+            .map(|inning| team_box.line.runs_by_inning.get(inning).map_or("x".to_string(), |runs| runs.to_string()))
+            .map(|runs| format!("{runs:>3}"))
+            .collect::<String>();
+        format!("{team:<24}{runs_by_inning} {:>4} {:>3} {:>3}", team_box.line.runs, team_box.line.hits, team_box.line.errors)
+    };
+    let header = (1..=innings).map(|inning| format!("{inning:>3}")).collect::<String>();
+
+    let mut lines = vec![
+        format!("{:<24}{header} {:>4} {:>3} {:>3}", "", "R", "H", "E"),
+        line_score(&box_score.visiting_team, &box_score.visiting),
+        line_score(&box_score.home_team, &box_score.home),
+    ];
+    for (team, team_box) in [(&box_score.visiting_team, &box_score.visiting), (&box_score.home_team, &box_score.home)] {
+        lines.push(String::new());
+        lines.extend(describe_team_box(team, team_box, all_players));
+    }
+    if let Some(decisions) = box_score.decisions() {
+        let player_name = |player: &PlayerId| all_players.get(player).map_or("(gone)", |player| player.name.as_str());
+        let mut credits = vec![format!("W: {}", player_name(&decisions.win)), format!("L: {}", player_name(&decisions.loss))];
+        if let Some(save) = decisions.save {
+            credits.push(format!("S: {}", player_name(&save)));
+        }
+        if !decisions.holds.is_empty() {
+            credits.push(format!("HLD: {}", decisions.holds.iter().map(player_name).collect::<Vec<_>>().join(", ")));
+        }
+        lines.push(String::new());
+        lines.push(credits.join(". "));
+    }
+
+    lines.join("\n")
+}
+
+fn describe_team_box(team: &str, team_box: &TeamBox, all_players: &HashMap<PlayerId, Player>) -> Vec<String> {
+    let player_name = |player: &PlayerId| all_players.get(player).map_or("(gone)", |player| player.name.as_str());
+
+    let mut lines = vec![format!("{team:<24} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4}", "AB", "R", "H", "RBI", "BB", "SO")];
+    lines.extend(team_box.batting.iter().map(|line| format!(
+        "{:<24} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4}",
+        player_name(&line.player),
+        line.at_bats,
+        line.runs,
+        line.hits,
+        line.runs_batted_in,
+        line.walks,
+        line.strikeouts,
+    )));
+
+    lines.push(format!("{:<24} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4}", "Pitching", "IP", "H", "R", "ER", "BB", "SO", "P"));
+    lines.extend(team_box.pitching.iter().map(|line| format!(
+        "{:<24} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4} {:>4}",
+        player_name(&line.player),
+        line.innings_pitched(),
+        line.hits,
+        line.runs,
+        line.earned_runs,
+        line.walks,
+        line.strikeouts,
+        line.pitches,
+    )));
+
+    lines
+}
+
+/// Writes a stat the way it's usually quoted: averages without the leading zero, other rates to two places and counts as whole numbers.
+pub fn format_stat(category: StatCategory, value: f64) -> String {
+    match category {
+        StatCategory::BattingAverage | StatCategory::OnBasePercentage | StatCategory::SluggingPercentage | StatCategory::OnBasePlusSlugging => {
+            let formatted = format!("{value:.3}");
+            formatted.strip_prefix('0').map_or(formatted.clone(), str::to_string)
+        },
+        StatCategory::EarnedRunAverage | StatCategory::WalksAndHitsPerInning | StatCategory::StrikeoutsPerNine => format!("{value:.2}"),
+        _ => format!("{value:.0}"),
+    }
+}
+
+/// Writes a batch's league-wide rates one to a line, each next to the range the major leagues land in.
+pub fn describe_batch_totals(totals: &BatchTotals) -> String {
+    let format_batch_stat = |stat: BatchStat, value: f64| match stat {
+        BatchStat::BattingAverage => format_stat(StatCategory::BattingAverage, value),
+        BatchStat::RunsPerGame => format!("{value:.2}"),
+        _ => format!("{:.1}%", value * 100.0),
+    };
+
+    let mut lines = vec![format!("{} games, {} plate appearances", totals.games, totals.plate_appearances())];
+    for stat in BatchStat::ALL {
+        let value = totals.value(stat);
+        let range = stat.mlb_range();
+        let verdict = if value < *range.start() {
+            "too low"
+        } else if value > *range.end() {
+            "too high"
         } else {
-            Self::Pitch
+            "ok"
+        };
+        lines.push(format!(
+            "{:<16} {:>7}   MLB {} to {}   {verdict}",
+            stat.name(),
+            format_batch_stat(stat, value),
+            format_batch_stat(stat, *range.start()),
+            format_batch_stat(stat, *range.end()),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+pub fn describe_leaders(leaders: &[(StatCategory, Vec<(PlayerId, f64)>)], season_stats: &HashMap<PlayerId, PlayerStats>) -> String {
+    let mut lines = Vec::new();
+    for (category, category_leaders) in leaders {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(category.abbreviation().to_string());
+        if category_leaders.is_empty() {
+            lines.push("  nobody yet".to_string());
+        }
+        for (rank, (player, value)) in category_leaders.iter().enumerate() {
+            let stats = season_stats.get(player).unwrap();
+            lines.push(format!("{:>3}. {:<24} {:<24} {:>6}", rank + 1, stats.name, stats.team, format_stat(*category, *value)));
         }
     }
+
+    lines.join("\n")
+}
+
+pub fn describe_award(kind: AwardKind, league: &str, stats: &PlayerStats) -> String {
+    let (batting, pitching) = (&stats.batting, &stats.pitching);
+    let numbers = match kind {
+        AwardKind::MostValuablePlayer => format!(
+            "hitting {}/{}/{} with {} home runs and {} RBI",
+            format_stat(StatCategory::BattingAverage, batting.batting_average()),
+            format_stat(StatCategory::OnBasePercentage, batting.on_base_percentage()),
+            format_stat(StatCategory::SluggingPercentage, batting.slugging_percentage()),
+            batting.home_runs,
+            batting.runs_batted_in,
+        ),
+        AwardKind::CyYoung => format!(
+            "going {}-{} with {} strikeouts and an ERA of {}",
+            pitching.wins,
+            pitching.losses,
+            pitching.strikeouts,
+            format_stat(StatCategory::EarnedRunAverage, pitching.earned_run_average()),
+        ),
+    };
+    format!("{} of the {} wins the {league} {} after {numbers}.", stats.name, stats.team, kind.name())
 }
 
 #[derive(Debug)]
 pub struct TextEngine {
-    all_players: HashMap<String, Player>,
+    all_players: HashMap<PlayerId, Player>,
+    season_stats: HashMap<PlayerId, PlayerStats>, // as they stood when the game started
     home_team_name: String,
     visiting_team_name: String,
 }
 
 impl TextEngine {
     pub fn new(
-        all_players: HashMap<String, Player>,
+        all_players: HashMap<PlayerId, Player>,
+        season_stats: HashMap<PlayerId, PlayerStats>,
         home_team_name: String,
         visiting_team_name: String,
     ) -> Self {
         Self {
             all_players,
+            season_stats,
             home_team_name,
             visiting_team_name,
         }
     }
 
+    /// Returns the name of the given player, or "(gone)" if they have since left the league.
+    fn player_name(&self, player: PlayerId) -> &str {
+        self.all_players.get(&player).map_or("(gone)", |player| player.name.as_str())
+    }
+
+    /// Returns a clause about how the batter has hit this season, once they've been up often enough for it to mean something.
+    fn describe_batting_average(&self, batter: PlayerId) -> String {
+        match self.season_stats.get(&batter) {
+            Some(stats) if stats.batting.at_bats >= MIN_AT_BATS_TO_QUOTE_AVERAGE => format!(
+                ", who's hitting {} on the year",
+                format_stat(StatCategory::BattingAverage, stats.batting.batting_average()),
+            ),
+            _ => String::new(),
+        }
+    }
+
     fn describe_strike(
         &self,
-        prev_game_state_summary: &GameStateSummary,
+        _prev_game_state_summary: &GameStateSummary,
         events_summary: &EventsSummary,
         new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
@@ -66,9 +532,9 @@ impl TextEngine {
 
     fn describe_ball(
         &self,
-        prev_game_state_summary: &GameStateSummary,
-        events_summary: &EventsSummary,
-        new_game_state_summary: &GameStateSummary,
+        _prev_game_state_summary: &GameStateSummary,
+        _events_summary: &EventsSummary,
+        _new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
     ) {
         sentences.push("Ball.".to_string());
@@ -102,19 +568,19 @@ impl TextEngine {
         &self,
         prev_game_state_summary: &GameStateSummary,
         events_summary: &EventsSummary,
-        new_game_state_summary: &GameStateSummary,
+        _new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
     ) {
         let sentence = match events_summary.batter_decision {
             BatterDecision::Swing => format!(
                 "He swings and he misses, {} strikes out {}.",
-                prev_game_state_summary.pitcher,
-                prev_game_state_summary.batter,
+                self.player_name(prev_game_state_summary.pitcher),
+                self.player_name(prev_game_state_summary.batter),
             ),
             BatterDecision::Take => format!(
                 "Strike three, {} strikes out {}.",
-                prev_game_state_summary.pitcher,
-                prev_game_state_summary.batter,
+                self.player_name(prev_game_state_summary.pitcher),
+                self.player_name(prev_game_state_summary.batter),
             ),
         };
         sentences.push(sentence);
@@ -128,30 +594,30 @@ impl TextEngine {
         sentences: &mut Vec<String>,
     ) {
         let sentence = if events_summary.runner_advancements.len() == 1 { // walk, no other runners
-            format!("Ball four, {} walks.", prev_game_state_summary.batter)
+            format!("Ball four, {} walks.", self.player_name(prev_game_state_summary.batter))
         } else { // walk into home
             if let Some(runner_advancement) = events_summary.runner_advancements.iter().find(|runner_advancement| runner_advancement.to_base == Some(Base::Home)) {
                 let part_1 = format!(
                     "Ball four, {} cycles the bases and walks in a run by {}",
-                    prev_game_state_summary.batter,
-                    runner_advancement.name,
+                    self.player_name(prev_game_state_summary.batter),
+                    self.player_name(runner_advancement.player),
                 );
                 let part_2 = format!(
                     "Now {} on first, {} on second, and {} on third.",
-                    new_game_state_summary.bases.first.as_ref().unwrap(),
-                    new_game_state_summary.bases.second.as_ref().unwrap(),
-                    new_game_state_summary.bases.third.as_ref().unwrap(),
+                    self.player_name(new_game_state_summary.bases.first.unwrap().player),
+                    self.player_name(new_game_state_summary.bases.second.unwrap().player),
+                    self.player_name(new_game_state_summary.bases.third.unwrap().player),
                 );
 
-                vec![part_1, part_2].join(" ")
+                [part_1, part_2].join(" ")
             } else if new_game_state_summary.bases.are_loaded() { // walks bases loaded
                 format!(
                     "Ball four, {} walks and {} has loaded the bases.",
-                    prev_game_state_summary.batter,
-                    prev_game_state_summary.pitcher,
+                    self.player_name(prev_game_state_summary.batter),
+                    self.player_name(prev_game_state_summary.pitcher),
                 )
             } else { // walk with other runners
-                let mut sentence = format!("Ball four, {} walks, ",  prev_game_state_summary.batter);
+                let mut sentence = format!("Ball four, {} walks, ",  self.player_name(prev_game_state_summary.batter));
                 let mut runner_parts = Vec::new();
                 for runner_advancement in events_summary.runner_advancements.iter() {
                     if runner_advancement.from_base != Base::Batting {
@@ -162,7 +628,7 @@ impl TextEngine {
                             Base::Home => "home",
                             Base::Batting => unreachable!(),
                         };
-                        runner_parts.push(format!("{} to {to_base}", runner_advancement.name));
+                        runner_parts.push(format!("{} to {to_base}", self.player_name(runner_advancement.player)));
                     }
                 }
                 sentence += &runner_parts.join(" and ");
@@ -176,7 +642,7 @@ impl TextEngine {
 
     fn describe_runner_advancements(
         &self,
-        runner_advancements: &Vec<RunnerAdvancement>,
+        runner_advancements: &[RunnerAdvancement],
         sentences: &mut Vec<String>,
         skip_batter: bool,
     ) {
@@ -187,10 +653,10 @@ impl TextEngine {
             }
 
             let part = match runner_advancement.to_base {
-                Some(Base::First) => format!("{} to first", runner_advancement.name),
-                Some(Base::Second) => format!("{} to second", runner_advancement.name),
-                Some(Base::Third) => format!("{} to third", runner_advancement.name),
-                Some(Base::Home) => format!("{} scores", runner_advancement.name),
+                Some(Base::First) => format!("{} to first", self.player_name(runner_advancement.player)),
+                Some(Base::Second) => format!("{} to second", self.player_name(runner_advancement.player)),
+                Some(Base::Third) => format!("{} to third", self.player_name(runner_advancement.player)),
+                Some(Base::Home) => format!("{} scores", self.player_name(runner_advancement.player)),
                 None => {
                     let end_base = match runner_advancement.from_base.next().unwrap() {
                         Base::First => "first",
@@ -199,7 +665,7 @@ impl TextEngine {
                         Base::Home => "home plate",
                         Base::Batting => unreachable!(),
                     };
-                    format!("{} is out at {}", runner_advancement.name, end_base)
+                    format!("{} is out at {}", self.player_name(runner_advancement.player), end_base)
                 },
                 Some(Base::Batting) => unreachable!(),
             };
@@ -212,9 +678,9 @@ impl TextEngine {
 
     fn describe_base_hit(
         &self,
-        prev_game_state_summary: &GameStateSummary,
+        _prev_game_state_summary: &GameStateSummary,
         events_summary: &EventsSummary,
-        new_game_state_summary: &GameStateSummary,
+        _new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
     ) {
         let hit_sentence = match events_summary.at_bat_outcome.as_ref().unwrap() {
@@ -234,7 +700,7 @@ impl TextEngine {
         &self,
         prev_game_state_summary: &GameStateSummary,
         events_summary: &EventsSummary,
-        new_game_state_summary: &GameStateSummary,
+        _new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
     ) {
         if events_summary.runner_advancements.len() == 1 {
@@ -244,18 +710,18 @@ impl TextEngine {
         } else {
             let num_runs = events_summary.runner_advancements.len();
             let team_name = if prev_game_state_summary.half_inning.top { &self.visiting_team_name } else { &self.home_team_name };
-            sentences.push(format!("Home run! And {} brings in {num_runs} runs for the {team_name}.", prev_game_state_summary.batter));
+            sentences.push(format!("Home run! And {} brings in {num_runs} runs for the {team_name}.", self.player_name(prev_game_state_summary.batter)));
         }
     }
 
     fn describe_batter_out(
         &self,
-        prev_game_state_summary: &GameStateSummary,
+        _prev_game_state_summary: &GameStateSummary,
         events_summary: &EventsSummary,
-        new_game_state_summary: &GameStateSummary,
+        _new_game_state_summary: &GameStateSummary,
         sentences: &mut Vec<String>,
     ) {
-        let sentence = format!("He hits a ground ball to the left, and... he'll be out at first base.");
+        let sentence = "He hits a ground ball to the left, and... he'll be out at first base.".to_string();
         sentences.push(sentence);
 
         if events_summary.runner_advancements.len() > 1 {
@@ -304,6 +770,45 @@ impl TextEngine {
         }
     }
 
+    fn team_name(&self, side: TeamSide) -> &str {
+        match side {
+            TeamSide::Home => &self.home_team_name,
+            TeamSide::Visiting => &self.visiting_team_name,
+        }
+    }
+
+    fn describe_demonic_events(
+        &self,
+        prev_game_state_summary: &GameStateSummary,
+        events_summary: &EventsSummary,
+        sentences: &mut Vec<String>,
+    ) {
+        let batting_side = if prev_game_state_summary.half_inning.top { TeamSide::Visiting } else { TeamSide::Home };
+
+        for event in events_summary.demonic_events.iter() {
+            let sentence = match event.kind {
+                DemonicEventKind::FlamingBall => if event.favored_side == batting_side {
+                    "Wait, that ball burst into flames off the bat, and nobody out there wants to lay a glove on it!".to_string()
+                } else {
+                    "Wait, that ball burst into flames off the bat and crumbled to ash before it got anywhere!".to_string()
+                },
+                DemonicEventKind::PhasingRunner => {
+                    let runner = self.player_name(event.player.unwrap());
+                    if event.favored_side == batting_side {
+                        format!("Hold on, the tag went right through {runner} like he wasn't there, and the umpire has no choice but to call him safe!")
+                    } else {
+                        format!("Hold on, {runner} has sunk clean through the bag up to his knees, and they tag him out!")
+                    }
+                },
+                DemonicEventKind::RainOfFrogs => format!(
+                    "And now it's raining frogs. Thousands of them. The grounds crew are out with shovels and we're in a delay, and the {} look badly shaken by it.",
+                    self.team_name(event.favored_side.other()),
+                ),
+            };
+            sentences.push(sentence);
+        }
+    }
+
     pub fn describe_pitch_level_summaries(
         &self,
         prev_game_state_summary: &GameStateSummary,
@@ -315,9 +820,10 @@ impl TextEngine {
         // count description
         if prev_game_state_summary.count.is_empty() {
             sentences.push(format!(
-                "{} pitching to {}.",
-                prev_game_state_summary.pitcher,
-                prev_game_state_summary.batter,
+                "{} pitching to {}{}.",
+                self.player_name(prev_game_state_summary.pitcher),
+                self.player_name(prev_game_state_summary.batter),
+                self.describe_batting_average(prev_game_state_summary.batter),
             ));
         } else if prev_game_state_summary.count.is_full() {
             sentences.push("It's a full count.".to_string());
@@ -346,32 +852,201 @@ impl TextEngine {
             );
         }
 
+        if let Some(mimed_side) = events_summary.mimed_side {
+            sentences.push(self.describe_mimed_play(prev_game_state_summary, mimed_side));
+        }
+        self.describe_demonic_events(prev_game_state_summary, events_summary, &mut sentences);
+
+        // end of game
+        match events_summary.game_outcome {
+            GameOutcome::HomeTeamWins => sentences.push(format!(
+                "And that's the ballgame, the {} win it {}-{}.",
+                self.home_team_name,
+                new_game_state_summary.home_team_runs,
+                new_game_state_summary.visiting_team_runs,
+            )),
+            GameOutcome::VisitingTeamWins => sentences.push(format!(
+                "And that's the ballgame, the {} win it {}-{}.",
+                self.visiting_team_name,
+                new_game_state_summary.visiting_team_runs,
+                new_game_state_summary.home_team_runs,
+            )),
+            GameOutcome::Ongoing => {},
+        }
+
+        sentences.join(" ")
+    }
+
+    pub fn describe_demon_power(&self, effect: &DemonPowerEffect, backfired: bool) -> String {
+        match effect {
+            DemonPowerEffect::InvisibleWalls { side, plays } => self.describe_i_me_mime(*side, *plays, backfired),
+        }
+    }
+
+    pub fn describe_sacrificed_out(&self, prev_game_state_summary: &GameStateSummary, new_game_state_summary: &GameStateSummary) -> String {
+        let mut sentences = vec![format!(
+            "{} sets the bat down, kneels in the box and offers his out to Rorzagoth. The umpire doesn't argue.",
+            self.player_name(prev_game_state_summary.batter),
+        )];
+        if new_game_state_summary.half_inning.top != prev_game_state_summary.half_inning.top {
+            sentences.push("And that's the side.".to_string());
+        }
+
         sentences.join(" ")
     }
 
-    pub fn describe_half_inning_summaries(
+    pub fn describe_pitching_change(&self, side: TeamSide, pitcher: PlayerId) -> String {
+        format!(
+            "There's a pitching change for the {}: {} comes in from the bullpen.",
+            self.team_name(side),
+            self.player_name(pitcher),
+        )
+    }
+
+    /// Returns a remark on a play that swung the game, naming the side it swung towards and their chances now.
+    pub fn describe_win_probability_swing(&self, prev_home_win_probability: f64, home_win_probability: f64) -> String {
+        let (side, win_probability) = if home_win_probability > prev_home_win_probability {
+            (TeamSide::Home, home_win_probability)
+        } else {
+            (TeamSide::Visiting, 1.0 - home_win_probability)
+        };
+        format!(
+            "That swings it towards the {}, who now have a {:.0}% chance of winning.",
+            self.team_name(side),
+            win_probability * 100.0,
+        )
+    }
+
+    fn describe_i_me_mime(&self, mimed_side: TeamSide, plays: u8, backfired: bool) -> String {
+        let team_name = self.team_name(mimed_side);
+        let plays_part = if plays == 1 { "the next play".to_string() } else { format!("the next {plays} plays") };
+
+        let mut sentences = Vec::new();
+        if backfired {
+            sentences.push("Something's gone wrong here, the air around our own dugout is shimmering.".to_string());
+        } else {
+            sentences.push("The lights flicker and a low laugh rolls across the stadium.".to_string());
+        }
+        sentences.push(format!(
+            "Every one of the {team_name} is in whiteface now, red noses, striped shirts, pressing their palms against walls that aren't there.",
+        ));
+        sentences.push(format!("They're not going anywhere for {plays_part}."));
+
+        sentences.join(" ")
+    }
+
+    /// Describes how the invisible walls got in the way of a ball in play.
+    fn describe_mimed_play(&self, prev_game_state_summary: &GameStateSummary, mimed_side: TeamSide) -> String {
+        let batting_side = if prev_game_state_summary.half_inning.top { TeamSide::Visiting } else { TeamSide::Home };
+        let team_name = self.team_name(mimed_side);
+        if mimed_side == batting_side {
+            format!("The {team_name} feel their way along the walls between the bases, and nobody gets a step further than they have to.")
+        } else {
+            format!("The {team_name} fielders can only mime reaching for it from inside their boxes as it rolls on by.")
+        }
+    }
+
+    pub fn describe_play_summaries(
         &self,
         prev_game_state_summary: &GameStateSummary,
-        events_summaries: &Vec<EventsSummary>,
-        game_state_summaries: &Vec<GameStateSummary>,
+        events_summaries: &[EventsSummary],
+        game_state_summaries: &[GameStateSummary],
     ) -> String {
-        let mut game_state_summaries = game_state_summaries.clone();
+        let mut game_state_summaries = game_state_summaries.to_vec();
         game_state_summaries.insert(0, prev_game_state_summary.clone());
 
         let mut sentences = Vec::new();
 
-        for i in 1..events_summaries.len() {
-            let prev_game_state_summary = &game_state_summaries[i - 1];
-            let events_summary = &events_summaries[i];
-            let new_game_state_summary = &game_state_summaries[i];
+        for (i, events_summary) in events_summaries.iter().enumerate() {
+            let prev_game_state_summary = &game_state_summaries[i];
+            let new_game_state_summary = &game_state_summaries[i + 1];
 
             sentences.push(self.describe_pitch_level_summaries(
-                &prev_game_state_summary,
-                &events_summary,
-                &new_game_state_summary,
+                prev_game_state_summary,
+                events_summary,
+                new_game_state_summary,
             ));
         }
 
         sentences.join(" ")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::{Bases, Count, HalfInning};
+    use crate::test_support::runner;
+
+    fn state(number: u8, top: bool, (home_team_runs, visiting_team_runs): (u8, u8)) -> GameStateSummary {
+        GameStateSummary {
+            home_team_runs,
+            visiting_team_runs,
+            half_inning: HalfInning { number, top },
+            bases: Bases::new_empty(),
+            outs: 0,
+            count: Count::empty(),
+            batter: PlayerId(100),
+            pitcher: PlayerId(9),
+        }
+    }
+
+    #[test]
+    fn granularity_gets_finer_as_leverage_rises() {
+        let model = WinProbabilityModel::default();
+        let thresholds = GranularityThresholds::default();
+        let mut bases_loaded = state(9, false, (3, 4));
+        bases_loaded.bases = Bases { first: runner(0, 109), second: runner(1, 109), third: runner(2, 109) };
+        bases_loaded.outs = 2;
+        assert_eq!(Granularity::from_state_summary(&bases_loaded, &thresholds, &model), Granularity::Pitch);
+        assert_eq!(Granularity::from_state_summary(&state(1, true, (0, 0)), &thresholds, &model), Granularity::HalfInning);
+        assert_eq!(Granularity::from_state_summary(&state(8, true, (10, 0)), &thresholds, &model), Granularity::Game);
+
+        // each tier in turn as the thresholds drop below the first batter's leverage
+        let first_batter = state(1, true, (0, 0));
+        let leverage = model.leverage_index(&first_batter);
+        let tiers = (0..5)
+            .map(|above| {
+                let threshold = |i: usize| if i < above { leverage + 1.0 } else { leverage - 0.1 };
+                let thresholds = GranularityThresholds { pitch: threshold(0), at_bat: threshold(1), half_inning: threshold(2), inning: threshold(3) };
+                assert!(thresholds.are_in_order());
+                Granularity::from_state_summary(&first_batter, &thresholds, &model)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tiers, Granularity::ALL);
+    }
+
+    #[test]
+    fn forcing_a_granularity_ignores_the_leverage() {
+        let model = WinProbabilityModel::default();
+        let mut settings = GranularitySettings::default();
+        assert_eq!(settings.granularity(&state(8, true, (10, 0)), &model), Granularity::Game);
+        settings.forced = Some(Granularity::Pitch);
+        assert_eq!(settings.granularity(&state(8, true, (10, 0)), &model), Granularity::Pitch);
+    }
+
+    #[test]
+    fn thresholds_must_not_rise_or_go_negative() {
+        assert!(GranularityThresholds::default().are_in_order());
+        assert!(GranularityThresholds { pitch: 1.0, at_bat: 1.0, half_inning: 0.0, inning: 0.0 }.are_in_order());
+        assert!(!GranularityThresholds { pitch: 0.8, at_bat: 0.9, half_inning: 0.4, inning: 0.1 }.are_in_order());
+        assert!(!GranularityThresholds { pitch: 1.5, at_bat: 0.9, half_inning: 0.4, inning: -0.1 }.are_in_order());
+        assert!(!GranularityThresholds { pitch: f64::NAN, at_bat: 0.9, half_inning: 0.4, inning: 0.1 }.are_in_order());
+    }
+
+    #[test]
+    fn each_granularity_ends_where_expected() {
+        let start = state(3, true, (0, 0));
+        let mut next_batter = start.clone();
+        next_batter.batter = PlayerId(101);
+        let bottom_half = state(3, false, (0, 0));
+        let next_inning = state(4, true, (0, 0));
+
+        let ends = |granularity: Granularity| [&start, &next_batter, &bottom_half, &next_inning].map(|state| granularity.is_over(&start, state));
+        assert_eq!(ends(Granularity::Pitch), [true; 4]);
+        assert_eq!(ends(Granularity::AtBat), [false, true, true, true]);
+        assert_eq!(ends(Granularity::HalfInning), [false, false, true, true]);
+        assert_eq!(ends(Granularity::Inning), [false, false, false, true]);
+        assert_eq!(ends(Granularity::Game), [false; 4]);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::baseball::{Contract, GameRng, Player, PlayerId, Position};
+use crate::development::PEAK_AGE;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const MAX_ROSTER_SIZE: usize = 26;
+pub const MIN_SALARY: u32 = 700;
+const MAX_SALARY: u32 = 30_000;
+const TRADE_PREMIUM: f64 = 0.05; // how much more value an ai team wants back than it gives up
+
+/// A change to who plays for whom.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Transaction {
+    Trade {
+        team: String,
+        other_team: String,
+        sent: Vec<PlayerId>, // from `team` to `other_team`
+        received: Vec<PlayerId>,
+    },
+    Signing {
+        team: String,
+        player: PlayerId,
+        contract: Contract,
+    },
+    Release {
+        team: String,
+        player: PlayerId,
+    },
+    Retirement {
+        team: String,
+        player: PlayerId,
+    },
+}
+
+/// An entry in the league's transaction history. The description is written when the transaction happens, so it still reads right after the players involved are gone.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub season: u32, // counting from 1
+    pub day: usize,
+    pub transaction: Transaction,
+    pub description: String,
+}
+
+/// A trade the expos are putting together with another team.
+#[derive(Clone, Debug)]
+pub struct TradeDraft {
+    pub team: String,
+    pub offered: Vec<PlayerId>,
+    pub requested: Vec<PlayerId>,
+}
+
+impl TradeDraft {
+    pub fn new(team: String) -> Self {
+        Self {
+            team,
+            offered: Vec::new(),
+            requested: Vec::new(),
+        }
+    }
+
+    /// Pairs each offered player with a requested player in the same role, pitchers with pitchers and position players with position players, so that each can take the other's place on the roster. Returns None if the two sides don't match up.
+    pub fn pairings(&self, all_players: &HashMap<PlayerId, Player>) -> Option<Vec<(PlayerId, PlayerId)>> {
+        if self.offered.is_empty() || self.offered.len() != self.requested.len() {
+            return None;
+        }
+
+        let is_pitcher = |player: &PlayerId| all_players.get(player).unwrap().position == Position::Pitcher;
+        let mut requested = self.requested.clone();
+        let mut pairings = Vec::new();
+        for offered in self.offered.iter() {
+            let index = requested.iter().position(|player| is_pitcher(player) == is_pitcher(offered))?;
+            pairings.push((*offered, requested.remove(index)));
+        }
+
+        Some(pairings)
+    }
+}
+
+/// Scales a player's worth by their age: young players still have their best years ahead of them and veterans are on the way down.
+fn age_factor(age: u32) -> f64 {
+    if age <= PEAK_AGE {
+        1.0 + 0.02 * (PEAK_AGE - age) as f64
+    } else {
+        (1.0 - 0.06 * (age - PEAK_AGE) as f64).max(0.3)
+    }
+}
+
+/// Returns the salary a player would ask for on the open market.
+pub fn asking_salary(player: &Player) -> u32 {
+    let worth = player.metrics.overall(player.position) * age_factor(player.age);
+    (MIN_SALARY as f64 + worth.powi(3) * (MAX_SALARY - MIN_SALARY) as f64).round() as u32
+}
+
+/// Returns a contract of a random length at the player's asking salary.
+pub fn random_contract(player: &Player, rng: &mut GameRng) -> Contract {
+    Contract {
+        salary: asking_salary(player),
+        years: rng.random_range(1..=5),
+    }
+}
+
+/// Returns how much an ai front office thinks a player is worth: their ability, adjusted for age, less what is still owed on their contract.
+pub fn player_value(player: &Player) -> f64 {
+    let ability = player.metrics.overall(player.position) * age_factor(player.age);
+    let cost = player.contract.map_or(0.0, |contract| (contract.salary * contract.years) as f64 / 1_000_000.0);
+    ability - cost
+}
+
+/// Returns whether an ai team takes a trade, given what they would give up and what they would get.
+pub fn ai_accepts_trade(given: &[PlayerId], received: &[PlayerId], all_players: &HashMap<PlayerId, Player>) -> bool {
+    let total_value = |players: &[PlayerId]| players.iter().map(|player| player_value(all_players.get(player).unwrap())).sum::<f64>();
+    total_value(received) >= total_value(given) + TRADE_PREMIUM
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseball::PlayerMetrics;
+
+    /// Returns players 0 to 3 with the same metrics: 0 and 1 are 25 year old hitters, 2 is a 36 year old hitter and 3 is a 25 year old pitcher.
+    fn players() -> HashMap<PlayerId, Player> {
+        let metrics = PlayerMetrics::random(&mut GameRng::seed_from_u64(0));
+        [(25, Position::FirstBase), (25, Position::Catcher), (36, Position::FirstBase), (25, Position::Pitcher)].into_iter()
+            .enumerate()
+            .map(|(id, (age, position))| (PlayerId(id as u32), Player {
+                id: PlayerId(id as u32),
+                name: format!("Player {id}"),
+                position,
+                age,
+                potential: 0.5,
+                contract: None,
+                metrics: metrics.clone(),
+            }))
+            .collect()
+    }
+
+    #[test]
+    fn ai_wants_more_back_than_it_gives_up() {
+        let mut all_players = players();
+        assert!(ai_accepts_trade(&[PlayerId(2)], &[PlayerId(1)], &all_players));
+        assert!(!ai_accepts_trade(&[PlayerId(1)], &[PlayerId(2)], &all_players));
+
+        // an even swap isn't enough
+        let mut twin = all_players.get(&PlayerId(1)).unwrap().clone();
+        twin.id = PlayerId(4);
+        all_players.insert(PlayerId(4), twin);
+        assert!(!ai_accepts_trade(&[PlayerId(1)], &[PlayerId(4)], &all_players));
+
+        // but getting rid of a big contract is
+        all_players.get_mut(&PlayerId(1)).unwrap().contract = Some(Contract { salary: MAX_SALARY, years: 5 });
+        assert!(ai_accepts_trade(&[PlayerId(1)], &[PlayerId(4)], &all_players));
+    }
+
+    #[test]
+    fn trades_pair_pitchers_with_pitchers() {
+        let all_players = players();
+        let mut trade_draft = TradeDraft::new("Boston Red Sox".to_string());
+        assert_eq!(trade_draft.pairings(&all_players), None);
+
+        trade_draft.offered = vec![PlayerId(3), PlayerId(0)];
+        trade_draft.requested = vec![PlayerId(1)];
+        assert_eq!(trade_draft.pairings(&all_players), None);
+
+        trade_draft.requested.push(PlayerId(2));
+        assert_eq!(trade_draft.pairings(&all_players), None);
+
+        trade_draft.offered = vec![PlayerId(2), PlayerId(0)];
+        assert_eq!(trade_draft.pairings(&all_players), Some(vec![(PlayerId(2), PlayerId(1)), (PlayerId(0), PlayerId(2))]));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::analytics::{BaseOutState, RunExpectancy};
+use crate::baseball::{Bases, Count, GameStateSummary, Runner};
+
+const REGULATION_INNINGS: u8 = 9;
+const MAX_RUNS: usize = 15; // more runs than this in a half-inning are rare enough to count as this many
+pub const BIG_SWING: f64 = 0.15; // a change in win probability worth remarking on
+const AVERAGE_SWING: f64 = 0.036; // how far a plate appearance moves the win probability on average, either way, over games played out with the major league model
+
+/// Runs scored over the rest of a half-inning from each base/out state, on average, in recent major league play. Rows are indexed by `base_state` and columns by outs.
+pub const RUN_EXPECTANCY: [[f64; 3]; 8] = [
+    [0.481, 0.254, 0.098], // bases empty
+    [0.859, 0.509, 0.224], // first
+    [1.100, 0.664, 0.319], // second
+    [1.437, 0.884, 0.429], // first and second
+    [1.353, 0.950, 0.353], // third
+    [1.784, 1.130, 0.478], // first and third
+    [1.920, 1.352, 0.570], // second and third
+    [2.282, 1.520, 0.736], // bases loaded
+];
+
+/// The chance of scoring at least once over the rest of a half-inning from each base/out state, in recent major league play, laid out like `RUN_EXPECTANCY`.
+pub const SCORING_PROBABILITY: [[f64; 3]; 8] = [
+    [0.268, 0.155, 0.067],
+    [0.416, 0.265, 0.127],
+    [0.614, 0.397, 0.216],
+    [0.614, 0.410, 0.224],
+    [0.843, 0.653, 0.258],
+    [0.860, 0.637, 0.278],
+    [0.853, 0.681, 0.264],
+    [0.862, 0.660, 0.319],
+];
+
+/// What each count is worth to the batting side in runs, compared with 0-0, indexed by balls then strikes.
+const COUNT_RUN_VALUES: [[f64; 3]; 4] = [
+    [0.000, -0.043, -0.096],
+    [0.038, -0.015, -0.077],
+    [0.104, 0.036, -0.042],
+    [0.196, 0.121, 0.038],
+];
+
+/// How a plate appearance can end, as far as the leverage index is concerned.
+#[derive(Copy, Clone, Debug)]
+enum PlateAppearanceResult {
+    Out,
+    Walk,
+    Single,
+    Double,
+    Triple,
+    HomeRun,
+}
+
+/// How often plate appearances end each way in recent major league play.
+const PLATE_APPEARANCE_RESULTS: [(PlateAppearanceResult, f64); 6] = [
+    (PlateAppearanceResult::Out, 0.685),
+    (PlateAppearanceResult::Walk, 0.085),
+    (PlateAppearanceResult::Single, 0.150),
+    (PlateAppearanceResult::Double, 0.045),
+    (PlateAppearanceResult::Triple, 0.005),
+    (PlateAppearanceResult::HomeRun, 0.030),
+];
+
+/// Returns which bases are occupied as an index from 0 to 7, counting first as 1, second as 2 and third as 4.
+pub fn base_state(bases: &Bases) -> usize {
+    bases.first.is_some() as usize + 2 * bases.second.is_some() as usize + 4 * bases.third.is_some() as usize
+}
+
+/// The run environment win probabilities are worked out in: the runs expected and the chance of scoring from each base/out state, laid out like `RUN_EXPECTANCY`. The default is recent major league play, but the simulation scores a lot more than that, so a model can be calibrated from the runs seen in simulated games instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WinProbabilityModel {
+    pub run_expectancy: [[f64; 3]; 8],
+    pub scoring_probability: [[f64; 3]; 8],
+}
+
+impl Default for WinProbabilityModel {
+    fn default() -> Self {
+        Self { run_expectancy: RUN_EXPECTANCY, scoring_probability: SCORING_PROBABILITY }
+    }
+}
+
+impl WinProbabilityModel {
+    /// Returns a model of the run environment recorded in the given matrix, as from `Game::simulate_run_expectancy`. States it hasn't seen keep the major league figures.
+    pub fn calibrated(run_expectancy: &RunExpectancy) -> Self {
+        let mut model = Self::default();
+        for base_state in 0..8 {
+            for outs in 0..3 {
+                let state = BaseOutState { first: base_state & 1 != 0, second: base_state & 2 != 0, third: base_state & 4 != 0, outs: outs as u8 };
+                model.run_expectancy[base_state][outs] = run_expectancy.value(state);
+                model.scoring_probability[base_state][outs] = run_expectancy.scoring_probability(state);
+            }
+        }
+        model
+    }
+
+    /// Returns how likely the batting side is to score each number of runs over the rest of the half-inning. Past the first, each extra run is less likely than the last by a constant factor, chosen so that the runs expected and the chance of scoring match the model's figures for the base/out state, adjusted for the count.
+    pub fn runs_distribution(&self, bases: &Bases, outs: u8, count: &Count) -> [f64; MAX_RUNS + 1] {
+        let mut distribution = [0.0; MAX_RUNS + 1];
+        if outs >= 3 {
+            distribution[0] = 1.0;
+            return distribution;
+        }
+
+        let (base_state, outs) = (base_state(bases), outs as usize);
+        let run_expectancy = self.run_expectancy[base_state][outs];
+        let count_value = COUNT_RUN_VALUES[(count.balls as usize).min(3)][(count.strikes as usize).min(2)];
+        let scale = if run_expectancy > 0.0 { ((run_expectancy + count_value) / run_expectancy).max(0.0) } else { 1.0 };
+        let scoring_probability = (self.scoring_probability[base_state][outs] * scale).min(0.99);
+        let run_expectancy = run_expectancy * scale;
+        let ratio = if run_expectancy > 0.0 { (1.0 - scoring_probability / run_expectancy).clamp(0.0, 0.95) } else { 0.0 };
+
+        distribution[0] = 1.0 - scoring_probability;
+        for (runs, probability) in distribution.iter_mut().enumerate().skip(1) {
+            *probability = scoring_probability * (1.0 - ratio) * ratio.powi(runs as i32 - 1);
+        }
+        // whatever is left over goes to the biggest innings
+        distribution[MAX_RUNS] += 1.0 - distribution.iter().sum::<f64>();
+
+        distribution
+    }
+
+    /// Returns the home side's chances of winning from the given state, from 0 to 1. The rest of the current half-inning is played out from its bases, outs and count, and every half-inning after it from scratch, until the home side is ahead or behind after nine innings or more. A game still tied after that is even money, as neither side has any edge in the model.
+    pub fn home_win_probability(&self, state: &GameStateSummary) -> f64 {
+        let inning = state.half_inning.number;
+        let home_lead = state.home_team_runs as i32 - state.visiting_team_runs as i32;
+        // walk-offs end the game on the spot
+        if inning >= REGULATION_INNINGS && !state.half_inning.top && home_lead > 0 {
+            return 1.0;
+        }
+
+        // the chance of each home lead, from the smallest possible up
+        let (mut leads, mut smallest_lead) = (vec![1.0], home_lead);
+        let mut play_half = |runs: &[f64], top: bool| {
+            let mut next_leads = vec![0.0; leads.len() + MAX_RUNS];
+            for (i, lead_probability) in leads.iter().enumerate() {
+                for (scored, runs_probability) in runs.iter().enumerate() {
+                    let j = if top { i + MAX_RUNS - scored } else { i + scored };
+                    next_leads[j] += lead_probability * runs_probability;
+                }
+            }
+            leads = next_leads;
+            if top {
+                smallest_lead -= MAX_RUNS as i32;
+            }
+        };
+
+        play_half(&self.runs_distribution(&state.bases, state.outs, &state.count), state.half_inning.top);
+        let fresh_half = self.runs_distribution(&Bases::new_empty(), 0, &Count::empty());
+        if state.half_inning.top {
+            play_half(&fresh_half, false);
+        }
+        for _ in inning + 1..=REGULATION_INNINGS {
+            play_half(&fresh_half, true);
+            play_half(&fresh_half, false);
+        }
+
+        leads.iter()
+            .enumerate()
+            .map(|(i, probability)| match (smallest_lead + i as i32).signum() {
+                1 => *probability,
+                0 => probability / 2.0,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    /// Returns how much the plate appearance under way could swing the game, compared with an average one: 1 is average, 2 twice as tense and so on. It's the win probability the plate appearance is expected to move, either way, over the usual ways it can end, compared with how far an average one moves it under the major league model.
+    pub fn leverage_index(&self, state: &GameStateSummary) -> f64 {
+        let current_win_probability = self.home_win_probability(state);
+        let expected_swing = PLATE_APPEARANCE_RESULTS.iter()
+            .map(|(result, probability)| probability * (self.home_win_probability(&state_after(state, *result)) - current_win_probability).abs())
+            .sum::<f64>();
+
+        expected_swing / AVERAGE_SWING
+    }
+}
+
+/// Returns how the game stands after the plate appearance under way ends the given way. Outs don't move anyone up, walks only move up runners who are forced, singles score runners from second, and doubles score runners from first.
+fn state_after(state: &GameStateSummary, result: PlateAppearanceResult) -> GameStateSummary {
+    let mut state = state.clone();
+    state.count = Count::empty();
+    let [first, second, third] = [&state.bases.first, &state.bases.second, &state.bases.third].map(Option::is_some);
+    let (runs, [first, second, third]) = match result {
+        PlateAppearanceResult::Out => {
+            state.outs += 1;
+            return state;
+        },
+        PlateAppearanceResult::Walk => ((first && second && third) as u8, [true, second || first, third || (first && second)]),
+        PlateAppearanceResult::Single => (second as u8 + third as u8, [true, first, false]),
+        PlateAppearanceResult::Double => (second as u8 + third as u8, [false, true, first]),
+        PlateAppearanceResult::Triple => (first as u8 + second as u8 + third as u8, [false, false, true]),
+        PlateAppearanceResult::HomeRun => (1 + first as u8 + second as u8 + third as u8, [false, false, false]),
+    };
+
+    let runner = Runner { player: state.batter, responsible_pitcher: state.pitcher, earned: true };
+    let [first, second, third] = [first, second, third].map(|occupied| occupied.then_some(runner));
+    state.bases = Bases { first, second, third };
+    if state.half_inning.top {
+        state.visiting_team_runs += runs;
+    } else {
+        state.home_team_runs += runs;
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::baseball::{HalfInning, PlayerId};
+    use super::*;
+    use crate::Game;
+    use crate::league::LeagueStructure;
+
+    fn state(number: u8, top: bool, home_team_runs: u8, visiting_team_runs: u8) -> GameStateSummary {
+        GameStateSummary {
+            home_team_runs,
+            visiting_team_runs,
+            half_inning: HalfInning { number, top },
+            bases: Bases::new_empty(),
+            outs: 0,
+            count: Count::empty(),
+            batter: PlayerId(0),
+            pitcher: PlayerId(100),
+        }
+    }
+
+    #[test]
+    fn runs_distributions_add_up_and_match_the_run_expectancy() {
+        let model = WinProbabilityModel::default();
+        let distribution = model.runs_distribution(&Bases::new_empty(), 0, &Count::empty());
+        assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((distribution[0] - (1.0 - SCORING_PROBABILITY[0][0])).abs() < 1e-9);
+        let expected_runs = distribution.iter().enumerate().map(|(runs, probability)| runs as f64 * probability).sum::<f64>();
+        assert!((expected_runs - RUN_EXPECTANCY[0][0]).abs() < 0.01);
+        assert_eq!(model.runs_distribution(&Bases::new_empty(), 3, &Count::empty())[0], 1.0);
+    }
+
+    #[test]
+    fn win_probability_follows_the_game_state() {
+        let model = WinProbabilityModel::default();
+        assert!((model.home_win_probability(&state(1, true, 0, 0)) - 0.5).abs() < 1e-9);
+        assert!(model.home_win_probability(&state(9, true, 3, 0)) > 0.95);
+        assert!(model.home_win_probability(&state(9, false, 0, 3)) < 0.05);
+        assert_eq!(model.home_win_probability(&state(10, false, 1, 0)), 1.0);
+
+        // a runner on third with nobody out in a tied bottom of the ninth puts the home side well ahead
+        let mut runner_on_third = state(9, false, 2, 2);
+        runner_on_third.bases.third = Some(Runner { player: PlayerId(1), responsible_pitcher: PlayerId(100), earned: true });
+        assert!(model.home_win_probability(&runner_on_third) > 0.8);
+        runner_on_third.count = Count { balls: 0, strikes: 2 };
+        let behind_in_the_count = model.home_win_probability(&runner_on_third);
+        runner_on_third.count = Count { balls: 3, strikes: 0 };
+        assert!(model.home_win_probability(&runner_on_third) > behind_in_the_count);
+    }
+
+    #[test]
+    fn leverage_is_highest_late_in_close_games() {
+        let model = WinProbabilityModel::default();
+        let first_batter = model.leverage_index(&state(1, true, 0, 0));
+        assert!((0.7..0.9).contains(&first_batter));
+
+        let mut bases_loaded = state(9, false, 3, 4);
+        let runner = Runner { player: PlayerId(1), responsible_pitcher: PlayerId(100), earned: true };
+        bases_loaded.bases = Bases { first: Some(runner), second: Some(runner), third: Some(runner) };
+        bases_loaded.outs = 2;
+        assert!(model.leverage_index(&bases_loaded) > 3.0);
+
+        assert!(model.leverage_index(&state(8, true, 10, 0)) < 0.1);
+    }
+
+    #[test]
+    fn calibrating_to_the_simulation_makes_leads_less_safe() {
+        let game = Game::with_seed(LeagueStructure::default(), 5);
+        let run_expectancy = game.simulate_run_expectancy(30, 3, 5);
+        let model = WinProbabilityModel::calibrated(&run_expectancy);
+        let bases_empty = BaseOutState { first: false, second: false, third: false, outs: 0 };
+        assert_eq!(model.run_expectancy[0][0], run_expectancy.value(bases_empty));
+        assert_eq!(model.scoring_probability[0][0], run_expectancy.scoring_probability(bases_empty));
+
+        // the simulation scores far more than the majors, so runs come back more easily
+        assert!(model.run_expectancy[0][0] > RUN_EXPECTANCY[0][0]);
+        let three_run_lead = state(8, true, 3, 0);
+        assert!(model.home_win_probability(&three_run_lead) < WinProbabilityModel::default().home_win_probability(&three_run_lead));
+        assert!((model.home_win_probability(&state(1, true, 0, 0)) - 0.5).abs() < 1e-9);
+
+        let distribution = model.runs_distribution(&Bases::new_empty(), 0, &Count::empty());
+        assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(distribution.iter().all(|probability| probability.is_finite() && *probability >= 0.0));
+    }
+}